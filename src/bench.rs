@@ -0,0 +1,318 @@
+//! Benchmarks for the light system and level updates, run without opening a window with
+//! `cargo run --release -- --bench [filter]`. Only benchmarks with names containing the filter are
+//! run.
+
+use std::{
+    f64::consts::TAU,
+    hint,
+    time::{Duration, Instant},
+};
+
+use nalgebra::{Point2, UnitComplex, UnitVector2, point, vector};
+
+use crate::{
+    collections::slot_guard::SlotGuard,
+    input::{AnalogInput, SyntheticInputSource},
+    level::{
+        Level,
+        entity_tracker::entity::{GameAction, player::PlayerState},
+        filesystem::STORED_LEVELS,
+        light_grid::{AngleRange, LightGrid, Pixel, raycast},
+    },
+};
+
+pub const BENCH_FLAG: &str = "--bench";
+
+/// How long each benchmark runs for after warming up.
+const MEASUREMENT_TIME: Duration = Duration::from_millis(500);
+const WARM_UP_TIME: Duration = Duration::from_millis(100);
+
+/// How many open pixels rays are traced from in each level.
+const SAMPLE_POINTS: usize = 64;
+const RAYCAST_DIRECTIONS: usize = 16;
+/// The same as a player's view cone.
+const VIEW_CONE_WIDTH: f64 = TAU / 4.0;
+
+/// How many times the player is sent back in time, adding a past self each time.
+const LOOPS: usize = 8;
+const TICKS_PER_LOOP: usize = 300;
+
+/// Returns the filter if the benchmarks were asked for on the command line.
+pub fn requested() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+
+    (args.next()? == BENCH_FLAG).then(|| args.next().unwrap_or_default())
+}
+
+pub fn run(filter: &str) {
+    let mut levels = STORED_LEVELS
+        .files()
+        .filter_map(|file| file.path().to_str())
+        .collect::<Vec<_>>();
+    levels.sort_unstable();
+
+    for name in levels {
+        let mut level = Level::new(name.to_owned());
+
+        if let Err(error) = level.reset() {
+            println!("{name}: couldn't be loaded: {error}");
+            continue;
+        }
+
+        bench_light_grid(name, &mut level.light_grid, filter);
+
+        if format!("{name}/update_game").contains(filter) {
+            bench_update_game(name, level);
+        }
+    }
+}
+
+/// Runs `function` repeatedly and prints the average time per operation, where each call does
+/// `operations` operations.
+fn bench<T>(name: &str, filter: &str, operations: usize, mut function: impl FnMut() -> T) {
+    if !name.contains(filter) || operations == 0 {
+        return;
+    }
+
+    let start = Instant::now();
+
+    while start.elapsed() < WARM_UP_TIME {
+        hint::black_box(function());
+    }
+
+    let mut calls = 0;
+    let mut fastest = Duration::MAX;
+    let start = Instant::now();
+
+    while start.elapsed() < MEASUREMENT_TIME {
+        let call_start = Instant::now();
+        hint::black_box(function());
+        fastest = fastest.min(call_start.elapsed());
+        calls += 1;
+    }
+
+    let total = start.elapsed();
+    let per_operation = total / (calls * operations) as u32;
+
+    println!(
+        "{name}: {per_operation:?} per operation, fastest call {:?} ({calls} calls of \
+         {operations})",
+        fastest / operations as u32,
+    );
+}
+
+fn bench_light_grid(name: &str, light_grid: &mut LightGrid, filter: &str) {
+    let pixels = light_grid.pixels().clone();
+    let bounds = pixels.bounds();
+
+    let open_pixels = pixels
+        .iter()
+        .filter(|(_, pixel)| !pixel.blocks_light())
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let sample_points = open_pixels
+        .iter()
+        .step_by((open_pixels.len() / SAMPLE_POINTS).max(1))
+        .map(|index| index.map(|x| x as f64 + 0.5))
+        .collect::<Vec<Point2<f64>>>();
+
+    let directions = (0..RAYCAST_DIRECTIONS)
+        .map(|i| {
+            // Offset so that none of the rays are axis aligned.
+            UnitComplex::new((i as f64 + 0.3) / RAYCAST_DIRECTIONS as f64 * TAU)
+                * UnitVector2::new_normalize(vector![1.0, 0.0])
+        })
+        .collect::<Vec<_>>();
+
+    bench(&format!("{name}/regenerate_corners"), filter, 1, || {
+        let mut light_grid = LightGrid::from_pixels(pixels.clone());
+        light_grid.update_corners();
+        light_grid
+    });
+
+    // Like an elevator door opening and closing in the middle of the level.
+    let door = point![
+        bounds.left() + bounds.size.x as isize / 2,
+        bounds.top() + bounds.size.y as isize / 2
+    ];
+    let mut open = false;
+
+    light_grid.update_corners();
+
+    bench(&format!("{name}/update_corners"), filter, 1, || {
+        open = !open;
+
+        for x in door.x..door.x + 16 {
+            for y in door.y..door.y + 2 {
+                light_grid[point![x, y]] = if open { Pixel::None } else { Pixel::Solid };
+            }
+        }
+
+        light_grid.update_corners();
+    });
+
+    *light_grid = LightGrid::from_pixels(pixels);
+
+    bench(
+        &format!("{name}/raycast"),
+        filter,
+        sample_points.len() * directions.len(),
+        || {
+            for &origin in &sample_points {
+                for &direction in &directions {
+                    hint::black_box(raycast(
+                        |_, index| light_grid[index].blocks_light(),
+                        origin,
+                        direction,
+                        LightGrid::MAXIMUM_RAY_RANGE,
+                    ));
+                }
+            }
+        },
+    );
+
+    bench(
+        &format!("{name}/trace_light_from"),
+        filter,
+        sample_points.len(),
+        || {
+            for &origin in &sample_points {
+                hint::black_box(light_grid.trace_light_from(origin, None));
+            }
+        },
+    );
+
+    bench(
+        &format!("{name}/trace_light_from_cone"),
+        filter,
+        sample_points.len() * directions.len(),
+        || {
+            for &origin in &sample_points {
+                for &direction in &directions {
+                    let range = AngleRange::from_direction_and_width(direction, VIEW_CONE_WIDTH);
+
+                    hint::black_box(light_grid.trace_light_from(origin, Some(range)));
+                }
+            }
+        },
+    );
+}
+
+/// Plays the level with a player wandering in small circles, sending them back to the start every
+/// `TICKS_PER_LOOP` ticks as a loop elevator would, and reports how long ticks take as past selves
+/// pile up.
+fn bench_update_game(name: &str, mut level: Level) {
+    if level.input_readers.is_empty() {
+        return;
+    }
+
+    for loop_index in 0..LOOPS {
+        let inputs = (0..TICKS_PER_LOOP).map(|tick| {
+            let angle = loop_index as f64 * 1.3 + tick as f64 * 0.05;
+            let direction = UnitComplex::new(angle) * vector![1.0, 0.0];
+
+            AnalogInput {
+                movement: direction * 0.5,
+                aim: Some(direction),
+            }
+        });
+
+        level.input_sources.clear();
+        level.add_input_source(SyntheticInputSource::new(inputs));
+
+        let mut update_time = Duration::ZERO;
+        let mut paradox_time = Duration::ZERO;
+        let mut paradox_calls = 0;
+        let mut past_selves = 0;
+
+        for _ in 0..TICKS_PER_LOOP {
+            let start = Instant::now();
+            level.update();
+            update_time += start.elapsed();
+
+            let (time, recording) = time_paradox_levels(&mut level);
+            paradox_time += time;
+            paradox_calls += recording;
+            past_selves = past_selves.max(count_past_selves(&level));
+
+            if level.level_name != name {
+                println!("{name}/update_game: left the level, stopping");
+                return;
+            }
+        }
+
+        println!(
+            "{name}/update_game: loop {loop_index}, {past_selves} past selves, {:?} per tick, \
+             {:?} per paradox_level ({paradox_calls} calls)",
+            update_time / TICKS_PER_LOOP as u32,
+            paradox_time / paradox_calls.max(1) as u32,
+        );
+
+        send_back_in_time(&mut level);
+    }
+}
+
+/// Past selves that were paradoxed out of existence still count, since they're still updated.
+fn count_past_selves(level: &Level) -> usize {
+    level
+        .entities
+        .values()
+        .filter_map(|entity| entity.inner.as_player())
+        .filter(|player| player.state != PlayerState::Active)
+        .count()
+}
+
+/// Times `Player::paradox_level` for every past self that's still replaying, returning the total
+/// time and how many there were.
+fn time_paradox_levels(level: &mut Level) -> (Duration, usize) {
+    let mut time = Duration::ZERO;
+    let mut count = 0;
+
+    for key in level.entities.keys().collect::<Vec<_>>() {
+        let (entity, guard) = SlotGuard::new(&mut level.entities, key);
+
+        let Some(player) = entity.inner.as_player() else {
+            continue;
+        };
+
+        if player.state != PlayerState::Recording {
+            continue;
+        }
+
+        let start = Instant::now();
+        hint::black_box(player.paradox_level(level.frame, &guard, &level.light_grid));
+        time += start.elapsed();
+        count += 1;
+    }
+
+    (time, count)
+}
+
+/// Does what a loop elevator does to the active player, without needing one in the level.
+fn send_back_in_time(level: &mut Level) {
+    for &key in &level.input_readers {
+        let Some(entity) = level.entities.get_mut(key) else {
+            continue;
+        };
+
+        if entity
+            .inner
+            .as_player()
+            .is_none_or(|player| player.state != PlayerState::Active)
+        {
+            continue;
+        }
+
+        let Some(past) = level.soft_reset_state.get_mut(key) else {
+            continue;
+        };
+
+        entity.inner.travel_to_beginning(past);
+        level.soft_reset_state.insert(entity.clone());
+    }
+
+    if let Err(error) = level.evaluate_game_action(&GameAction::SoftReset) {
+        println!("Couldn't loop {}: {error}", level.level_name);
+    }
+}
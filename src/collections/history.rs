@@ -1,8 +1,10 @@
 use std::{mem, num::NonZero, ops::Range};
 
+use serde::{Deserialize, Serialize};
+
 pub type FrameIndex = usize;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct History<T> {
     data: Vec<Record<T>>,
 }
@@ -22,6 +24,14 @@ impl<T> History<T> {
         self.data.iter().map(|record| record.size()).sum()
     }
 
+    /// The range between the first and last recorded frames. There may be gaps inside of it.
+    pub fn range(&self) -> Range<FrameIndex> {
+        match (self.data.first(), self.data.last()) {
+            (Some(first), Some(last)) => first.start()..last.finish(),
+            _ => 0..0,
+        }
+    }
+
     pub fn get(&self, index: FrameIndex) -> Option<&T> {
         let record_index = match self
             .data
@@ -70,7 +80,7 @@ impl<T> History<T> {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 enum Record<T> {
     Constant {
         start: FrameIndex,
@@ -1,7 +1,334 @@
-use std::f64::consts::SQRT_2;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    f64::consts::SQRT_2,
+    fmt::{self, Display, Formatter},
+    sync::{LazyLock, Mutex, MutexGuard},
+};
 
-use macroquad::input::KeyCode;
+use macroquad::input::{KeyCode, MouseButton};
 use nalgebra::{Vector2, vector};
+use serde::{Deserialize, Serialize};
+
+/// A key or mouse button that an `Action` can be bound to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl From<KeyCode> for Binding {
+    fn from(key: KeyCode) -> Self {
+        Binding::Key(key)
+    }
+}
+
+impl From<MouseButton> for Binding {
+    fn from(button: MouseButton) -> Self {
+        Binding::Mouse(button)
+    }
+}
+
+impl Binding {
+    /// Keys are named as in `KeyCode`, and mouse buttons as `MouseLeft`, `MouseMiddle` and
+    /// `MouseRight`.
+    pub fn parse(name: &str) -> Option<Binding> {
+        if let Some(button) = name.strip_prefix("Mouse") {
+            return MOUSE_BUTTONS
+                .into_iter()
+                .find(|other| format!("{other:?}").eq_ignore_ascii_case(button))
+                .map(Binding::Mouse);
+        }
+
+        KEY_CODES
+            .into_iter()
+            .find(|other| format!("{other:?}").eq_ignore_ascii_case(name))
+            .map(Binding::Key)
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Mouse(button) => write!(f, "Mouse{button:?}"),
+        }
+    }
+}
+
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Middle, MouseButton::Right];
+
+#[rustfmt::skip]
+const KEY_CODES: [KeyCode; 120] = {
+    use KeyCode::*;
+    [
+        Space, Apostrophe, Comma, Minus, Period, Slash,
+        Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+        Semicolon, Equal,
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        LeftBracket, Backslash, RightBracket, GraveAccent, World1, World2,
+        Escape, Enter, Tab, Backspace, Insert, Delete, Right, Left, Down, Up,
+        PageUp, PageDown, Home, End, CapsLock, ScrollLock, NumLock, PrintScreen, Pause,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13,
+        F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, F25,
+        Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9,
+        KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual,
+        LeftShift, LeftControl, LeftAlt, LeftSuper, RightShift, RightControl, RightAlt, RightSuper,
+        Menu,
+    ]
+};
+
+/// Something the player can do that isn't tied to a particular key.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Action {
+    MoveRight,
+    MoveUp,
+    MoveLeft,
+    MoveDown,
+    RotateClockwise,
+    RotateCounterclockwise,
+    Restart,
+    Pause,
+    Fullscreen,
+    ToggleEditor,
+    EditorCancel,
+    EditorCursorLeft,
+    EditorCursorRight,
+    EditorCursorStart,
+    EditorCursorEnd,
+    EditorHistoryPrevious,
+    EditorHistoryNext,
+    LevelSelect,
+    Timeline,
+    TimelineEarlier,
+    TimelineLater,
+    ParadoxInspector,
+    DebugOverlay,
+    Quicksave,
+    Quickload,
+    SpeedrunTimer,
+    Ghost,
+    TimeSlower,
+    TimeFaster,
+    TimePause,
+    TimeStep,
+}
+
+impl Action {
+    pub const ALL: [Action; 31] = [
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveLeft,
+        Action::MoveDown,
+        Action::RotateClockwise,
+        Action::RotateCounterclockwise,
+        Action::Restart,
+        Action::Pause,
+        Action::Fullscreen,
+        Action::ToggleEditor,
+        Action::EditorCancel,
+        Action::EditorCursorLeft,
+        Action::EditorCursorRight,
+        Action::EditorCursorStart,
+        Action::EditorCursorEnd,
+        Action::EditorHistoryPrevious,
+        Action::EditorHistoryNext,
+        Action::LevelSelect,
+        Action::Timeline,
+        Action::TimelineEarlier,
+        Action::TimelineLater,
+        Action::ParadoxInspector,
+        Action::DebugOverlay,
+        Action::Quicksave,
+        Action::Quickload,
+        Action::SpeedrunTimer,
+        Action::Ghost,
+        Action::TimeSlower,
+        Action::TimeFaster,
+        Action::TimePause,
+        Action::TimeStep,
+    ];
+
+    /// The name used in the settings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveRight => "move_right",
+            Action::MoveUp => "move_up",
+            Action::MoveLeft => "move_left",
+            Action::MoveDown => "move_down",
+            Action::RotateClockwise => "rotate_clockwise",
+            Action::RotateCounterclockwise => "rotate_counterclockwise",
+            Action::Restart => "restart",
+            Action::Pause => "pause",
+            Action::Fullscreen => "fullscreen",
+            Action::ToggleEditor => "toggle_editor",
+            Action::EditorCancel => "editor_cancel",
+            Action::EditorCursorLeft => "editor_cursor_left",
+            Action::EditorCursorRight => "editor_cursor_right",
+            Action::EditorCursorStart => "editor_cursor_start",
+            Action::EditorCursorEnd => "editor_cursor_end",
+            Action::EditorHistoryPrevious => "editor_history_previous",
+            Action::EditorHistoryNext => "editor_history_next",
+            Action::LevelSelect => "level_select",
+            Action::Timeline => "timeline",
+            Action::TimelineEarlier => "timeline_earlier",
+            Action::TimelineLater => "timeline_later",
+            Action::ParadoxInspector => "paradox_inspector",
+            Action::DebugOverlay => "debug_overlay",
+            Action::Quicksave => "quicksave",
+            Action::Quickload => "quickload",
+            Action::SpeedrunTimer => "speedrun_timer",
+            Action::Ghost => "ghost",
+            Action::TimeSlower => "time_slower",
+            Action::TimeFaster => "time_faster",
+            Action::TimePause => "time_pause",
+            Action::TimeStep => "time_step",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_bindings(self) -> Vec<Binding> {
+        let keys: &[KeyCode] = match self {
+            Action::MoveRight => &[KeyCode::D, KeyCode::Right],
+            Action::MoveUp => &[KeyCode::W, KeyCode::Up],
+            Action::MoveLeft => &[KeyCode::A, KeyCode::Left],
+            Action::MoveDown => &[KeyCode::S, KeyCode::Down],
+            Action::RotateClockwise => &[KeyCode::E],
+            Action::RotateCounterclockwise => &[KeyCode::Q],
+            Action::Restart => &[KeyCode::R],
+            Action::Pause => &[KeyCode::Escape],
+            Action::Fullscreen => &[KeyCode::F11],
+            Action::ToggleEditor => &[KeyCode::F3],
+            Action::EditorCancel => &[KeyCode::Escape],
+            Action::EditorCursorLeft => &[KeyCode::Left],
+            Action::EditorCursorRight => &[KeyCode::Right],
+            Action::EditorCursorStart => &[KeyCode::Home],
+            Action::EditorCursorEnd => &[KeyCode::End],
+            Action::EditorHistoryPrevious => &[KeyCode::Up],
+            Action::EditorHistoryNext => &[KeyCode::Down],
+            Action::LevelSelect => &[KeyCode::Tab],
+            Action::Timeline => &[KeyCode::F4],
+            Action::TimelineEarlier => &[KeyCode::LeftBracket],
+            Action::TimelineLater => &[KeyCode::RightBracket],
+            Action::ParadoxInspector => &[KeyCode::F6],
+            Action::DebugOverlay => &[KeyCode::F2],
+            Action::Quicksave => &[KeyCode::F5],
+            Action::Quickload => &[KeyCode::F9],
+            Action::SpeedrunTimer => &[KeyCode::F7],
+            Action::Ghost => &[KeyCode::F8],
+            Action::TimeSlower => &[KeyCode::Minus],
+            Action::TimeFaster => &[KeyCode::Equal],
+            Action::TimePause => &[KeyCode::P],
+            Action::TimeStep => &[KeyCode::Period],
+        };
+
+        keys.iter().copied().map(Binding::Key).collect()
+    }
+}
+
+/// Which keys and mouse buttons trigger each `Action`. An action may have any number of bindings,
+/// and a binding may trigger more than one action.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KeyBindings {
+    pub bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn is_bound(&self, action: Action, input: impl Into<Binding>) -> bool {
+        self.get(action).contains(&input.into())
+    }
+
+    /// Bindings that can't be parsed are skipped, so a typo doesn't lose the whole line.
+    pub fn set_from_str(&mut self, action: Action, bindings: &str) {
+        self.bindings.insert(
+            action,
+            bindings
+                .split(',')
+                .map(str::trim)
+                .filter_map(Binding::parse)
+                .collect(),
+        );
+    }
+
+    pub fn bindings_to_string(&self, action: Action) -> String {
+        self.get(action)
+            .iter()
+            .map(Binding::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The bindings in use, so that inputs created deep inside entities can follow the settings.
+pub static KEY_BINDINGS: LazyLock<Mutex<KeyBindings>> =
+    LazyLock::new(|| Mutex::new(KeyBindings::default()));
+
+pub fn key_bindings() -> MutexGuard<'static, KeyBindings> {
+    KEY_BINDINGS.lock().unwrap()
+}
+
+/// How the player's view cone is aimed when no stick is aiming it.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Debug)]
+pub enum AimMode {
+    /// Looks towards the mouse.
+    #[default]
+    Mouse,
+    /// Turned with `Action::RotateClockwise` and `Action::RotateCounterclockwise`.
+    RotateKeys,
+    /// Snaps to the direction of movement.
+    Movement,
+}
+
+impl AimMode {
+    pub const ALL: [AimMode; 3] = [AimMode::Mouse, AimMode::RotateKeys, AimMode::Movement];
+
+    /// The name used in the settings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            AimMode::Mouse => "mouse",
+            AimMode::RotateKeys => "rotate_keys",
+            AimMode::Movement => "movement",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<AimMode> {
+        AimMode::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    pub fn cycle(self, steps: i32) -> AimMode {
+        let index = AimMode::ALL
+            .iter()
+            .position(|&mode| mode == self)
+            .unwrap_or(0);
+
+        AimMode::ALL[(index as i32 + steps).rem_euclid(AimMode::ALL.len() as i32) as usize]
+    }
+}
+
+/// The aim mode in use. Read by players every update, so changing it takes effect immediately.
+pub static AIM_MODE: Mutex<AimMode> = Mutex::new(AimMode::Mouse);
+
+pub fn aim_mode() -> AimMode {
+    *AIM_MODE.lock().unwrap()
+}
 
 #[derive(Clone, Debug)]
 pub struct DirectionalInput {
@@ -11,26 +338,44 @@ pub struct DirectionalInput {
 
 impl Default for DirectionalInput {
     fn default() -> Self {
-        Self::new(KeyCode::D, KeyCode::W, KeyCode::A, KeyCode::S)
+        Self::from_key_bindings(&key_bindings())
     }
 }
 
 impl DirectionalInput {
-    pub fn new(right: KeyCode, up: KeyCode, left: KeyCode, down: KeyCode) -> DirectionalInput {
+    pub fn new(
+        right: Vec<Binding>,
+        up: Vec<Binding>,
+        left: Vec<Binding>,
+        down: Vec<Binding>,
+    ) -> DirectionalInput {
         DirectionalInput {
             x_axis: AxialInput::new(right, left),
             y_axis: AxialInput::new(down, up),
         }
     }
 
-    pub fn key_down(&mut self, key_down: KeyCode) {
-        self.x_axis.key_down(key_down.clone());
-        self.y_axis.key_down(key_down);
+    pub fn from_key_bindings(bindings: &KeyBindings) -> DirectionalInput {
+        DirectionalInput::new(
+            bindings.get(Action::MoveRight).to_vec(),
+            bindings.get(Action::MoveUp).to_vec(),
+            bindings.get(Action::MoveLeft).to_vec(),
+            bindings.get(Action::MoveDown).to_vec(),
+        )
     }
 
-    pub fn key_up(&mut self, key_up: KeyCode) {
-        self.x_axis.key_up(key_up.clone());
-        self.y_axis.key_up(key_up);
+    pub fn input_down(&mut self, input: impl Into<Binding>) {
+        let input = input.into();
+
+        self.x_axis.input_down(input);
+        self.y_axis.input_down(input);
+    }
+
+    pub fn input_up(&mut self, input: impl Into<Binding>) {
+        let input = input.into();
+
+        self.x_axis.input_up(input);
+        self.y_axis.input_up(input);
     }
 
     pub fn clear_keys_down(&mut self) {
@@ -76,82 +421,263 @@ impl DirectionalInput {
 
 #[derive(Clone, Debug)]
 pub struct AxialInput {
-    pub positive: KeyCode,
-    pub positive_down: bool,
-    pub negative: KeyCode,
-    pub negative_down: bool,
+    pub positive: Vec<Binding>,
+    /// How many of the positive bindings are held.
+    pub positive_down: u8,
+    pub negative: Vec<Binding>,
+    pub negative_down: u8,
     pub output: i8,
 }
 
 impl AxialInput {
-    pub fn new(positive: KeyCode, negative: KeyCode) -> AxialInput {
+    pub fn new(positive: Vec<Binding>, negative: Vec<Binding>) -> AxialInput {
         AxialInput {
             positive,
-            positive_down: false,
+            positive_down: 0,
             negative,
-            negative_down: false,
+            negative_down: 0,
             output: 0,
         }
     }
 
-    pub fn key_down(&mut self, key_down: KeyCode) {
-        if key_down == self.positive {
+    pub fn from_key_bindings(
+        bindings: &KeyBindings,
+        positive: Action,
+        negative: Action,
+    ) -> AxialInput {
+        AxialInput::new(
+            bindings.get(positive).to_vec(),
+            bindings.get(negative).to_vec(),
+        )
+    }
+
+    pub fn input_down(&mut self, input: impl Into<Binding>) {
+        let input = input.into();
+
+        if self.positive.contains(&input) {
             self.output = 1;
-            self.positive_down = true;
-        } else if key_down == self.negative {
+            self.positive_down += 1;
+        } else if self.negative.contains(&input) {
             self.output = -1;
-            self.negative_down = true;
+            self.negative_down += 1;
         }
     }
 
-    pub fn key_up(&mut self, key_up: KeyCode) {
-        if key_up == self.positive {
-            self.output = if self.negative_down { -1 } else { 0 };
-            self.positive_down = false;
-        } else if key_up == self.negative {
-            self.output = if self.positive_down { 1 } else { 0 };
-            self.negative_down = false;
+    pub fn input_up(&mut self, input: impl Into<Binding>) {
+        let input = input.into();
+
+        // Saturating, since keys held when the input was cleared are released afterwards.
+        if self.positive.contains(&input) {
+            self.positive_down = self.positive_down.saturating_sub(1);
+
+            if self.positive_down == 0 {
+                self.output = if self.negative_down > 0 { -1 } else { 0 };
+            }
+        } else if self.negative.contains(&input) {
+            self.negative_down = self.negative_down.saturating_sub(1);
+
+            if self.negative_down == 0 {
+                self.output = if self.positive_down > 0 { 1 } else { 0 };
+            }
         }
     }
 
     pub fn clear_keys_down(&mut self) {
-        self.positive_down = false;
-        self.negative_down = false;
+        self.positive_down = 0;
+        self.negative_down = 0;
         self.output = 0;
     }
 
     pub fn stateless_output(&self) -> i8 {
-        self.positive_down as i8 - self.negative_down as i8
+        (self.positive_down > 0) as i8 - (self.negative_down > 0) as i8
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct ButtonInput {
-    pub key: KeyCode,
-    pub is_down: bool,
+    pub bindings: Vec<Binding>,
+    /// How many of the bindings are held.
+    pub held: u8,
 }
 
 impl ButtonInput {
-    pub fn new(key: KeyCode) -> ButtonInput {
-        ButtonInput {
-            key,
-            is_down: false,
-        }
+    pub fn new(bindings: Vec<Binding>) -> ButtonInput {
+        ButtonInput { bindings, held: 0 }
+    }
+
+    pub fn from_key_bindings(bindings: &KeyBindings, action: Action) -> ButtonInput {
+        ButtonInput::new(bindings.get(action).to_vec())
     }
 
-    pub fn key_down(&mut self, key_down: KeyCode) {
-        if self.key == key_down {
-            self.is_down = true;
+    pub fn is_down(&self) -> bool {
+        self.held > 0
+    }
+
+    pub fn input_down(&mut self, input: impl Into<Binding>) {
+        if self.bindings.contains(&input.into()) {
+            self.held += 1;
         }
     }
 
-    pub fn key_up(&mut self, key_up: KeyCode) {
-        if self.key == key_up {
-            self.is_down = false;
+    pub fn input_up(&mut self, input: impl Into<Binding>) {
+        if self.bindings.contains(&input.into()) {
+            self.held = self.held.saturating_sub(1);
         }
     }
 
     pub fn clear_keys_down(&mut self) {
-        self.is_down = false;
+        self.held = 0;
+    }
+}
+
+/// Analog input read from an `InputSource` once per update.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AnalogInput {
+    /// Within the unit circle, with positive y pointing down.
+    pub movement: Vector2<f64>,
+    /// Where to look, relative to the player. `None` leaves the view direction to the mouse.
+    pub aim: Option<Vector2<f64>>,
+}
+
+impl Default for AnalogInput {
+    fn default() -> Self {
+        Self {
+            movement: vector![0.0, 0.0],
+            aim: None,
+        }
+    }
+}
+
+impl AnalogInput {
+    /// Sticks pushed less than this far are treated as centered.
+    pub const DEAD_ZONE: f64 = 0.2;
+
+    /// Applies the dead zone to raw stick positions.
+    pub fn from_sticks(movement: Vector2<f64>, aim: Vector2<f64>) -> AnalogInput {
+        let movement = if movement.norm() < Self::DEAD_ZONE {
+            vector![0.0, 0.0]
+        } else if movement.norm() > 1.0 {
+            movement.normalize()
+        } else {
+            movement
+        };
+
+        AnalogInput {
+            movement,
+            aim: (aim.norm() >= Self::DEAD_ZONE).then(|| aim.normalize()),
+        }
+    }
+
+    /// Movement from every source is added together, and the first source that aims wins.
+    pub fn combine(self, other: AnalogInput) -> AnalogInput {
+        let movement = self.movement + other.movement;
+
+        AnalogInput {
+            movement: if movement.norm() > 1.0 {
+                movement.normalize()
+            } else {
+                movement
+            },
+            aim: self.aim.or(other.aim),
+        }
+    }
+}
+
+/// A device that is polled for analog input once per update, such as a gamepad. Keyboard and mouse
+/// input arrive as events instead, and are handled alongside these.
+pub trait InputSource {
+    fn poll(&mut self) -> AnalogInput;
+}
+
+/// An `InputSource` that plays back queued inputs, one per update, then holds the last one. Lets
+/// the game be driven without any device.
+#[derive(Clone, Default, Debug)]
+pub struct SyntheticInputSource {
+    pub queue: VecDeque<AnalogInput>,
+    pub current: AnalogInput,
+}
+
+impl SyntheticInputSource {
+    pub fn new(inputs: impl IntoIterator<Item = AnalogInput>) -> SyntheticInputSource {
+        SyntheticInputSource {
+            queue: inputs.into_iter().collect(),
+            current: AnalogInput::default(),
+        }
+    }
+
+    pub fn push(&mut self, input: AnalogInput) {
+        self.queue.push_back(input);
+    }
+}
+
+impl InputSource for SyntheticInputSource {
+    fn poll(&mut self) -> AnalogInput {
+        if let Some(next) = self.queue.pop_front() {
+            self.current = next;
+        }
+
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    use super::*;
+    use crate::level::{Level, entity_tracker::entity::player::PlayerState};
+
+    fn active_player_state(level: &Level) -> (Point2<f64>, Vector2<f64>) {
+        let player = level
+            .entities
+            .values()
+            .filter_map(|entity| entity.inner.as_player())
+            .find(|player| player.state == PlayerState::Active)
+            .unwrap();
+
+        (player.position, player.view_direction.into_inner())
+    }
+
+    #[test]
+    fn synthetic_input_moves_and_aims_player() {
+        let mut level = Level::new("start".to_owned());
+        level.reset().unwrap();
+
+        // Right, then down, aiming up the whole time.
+        level.add_input_source(SyntheticInputSource::new(
+            [vector![1.0, 0.0]; 8]
+                .into_iter()
+                .chain([vector![0.0, 1.0]; 8])
+                .map(|movement| AnalogInput {
+                    movement,
+                    aim: Some(vector![0.0, -1.0]),
+                }),
+        ));
+
+        let (start, _) = active_player_state(&level);
+
+        for _ in 0..8 {
+            level.update();
+        }
+
+        let (moved_right, view_direction) = active_player_state(&level);
+
+        assert!(moved_right.x > start.x, "{start} {moved_right}");
+        assert!(
+            (moved_right.y - start.y).abs() < 1e-9,
+            "{start} {moved_right}"
+        );
+        assert!(
+            (view_direction - vector![0.0, -1.0]).norm() < 1e-6,
+            "{view_direction}"
+        );
+
+        for _ in 0..8 {
+            level.update();
+        }
+
+        let (moved_down, _) = active_player_state(&level);
+
+        assert!(moved_down.y > moved_right.y, "{moved_right} {moved_down}");
     }
 }
@@ -5,6 +5,8 @@ use macroquad::{
     color::{Color, colors},
     input::{KeyCode, MouseButton},
     material,
+    math::{Mat4, vec3},
+    miniquad::{BlendFactor, BlendState, BlendValue, Equation},
     prelude::{Material, MaterialParams, PipelineParams, ShaderSource},
     shapes,
     texture::{self, DrawTextureParams, FilterMode, Image, Texture2D},
@@ -19,23 +21,49 @@ use crate::{
         slot_guard::SlotGuard,
         tile_grid::{TileGrid, TileIndex},
     },
+    input::{self, Action, AnalogInput, Binding, DirectionalInput, InputSource},
     level::{
         entity_tracker::{
             EntityTracker,
-            entity::{GameAction, ViewKind, elevator::ElevatorState, player::PlayerState},
+            entity::{
+                Entity, GameAction, ViewKind,
+                elevator::ElevatorState,
+                lamp,
+                player::{self, Player, PlayerState},
+            },
         },
         filesystem::{FileSystem, LoadLevelError},
+        ghost::Ghost,
         level_editor::LevelEditor,
-        light_grid::{LightGrid, Pixel},
+        level_select::LevelSelect,
+        level_stats::{LevelStats, SummaryCard},
+        light_grid::{LightArea, LightGrid, Pixel},
+        metadata::LevelMetadata,
+        progress::{LevelGraph, Progress},
+        speedrun::SpeedrunTimer,
         tile::{TILE_KINDS, Tile, TileKind},
+        time_control::TimeControl,
+        timeline::Timeline,
     },
 };
 
+pub(crate) mod debug_overlay;
 pub(crate) mod entity_tracker;
 pub(crate) mod filesystem;
+pub(crate) mod ghost;
+pub(crate) mod hud;
 pub(crate) mod level_editor;
+pub(crate) mod level_select;
+pub(crate) mod level_stats;
 pub(crate) mod light_grid;
+pub(crate) mod metadata;
+pub(crate) mod paradox_inspector;
+pub(crate) mod progress;
+pub(crate) mod save_state;
+pub(crate) mod speedrun;
 pub(crate) mod tile;
+pub(crate) mod time_control;
+pub(crate) mod timeline;
 
 pub const TILE_SIZE: isize = 8;
 
@@ -43,6 +71,9 @@ pub const UPDATE_TPS: usize = 60;
 pub const UPDATE_DT: f64 = 1.0 / UPDATE_TPS as f64;
 pub const MAX_UPDATES_PER_TICK: usize = 4;
 
+/// How long after the player dies before the loop restarts.
+pub const RESTART_DELAY: FrameIndex = UPDATE_TPS;
+
 pub const THE_END_TEXTURE_POSITION: Point2<f32> = point![0.0, 64.0];
 pub const THE_END_TEXTURE_SIZE: Vector2<f32> = vector![64.0, 16.0];
 
@@ -50,6 +81,17 @@ pub struct Level {
     pub filesystem: FileSystem,
     pub level_name: String,
     pub level_data: Option<Vec<u8>>,
+    pub metadata: LevelMetadata,
+    /// The level the title card was last shown for.
+    pub entered_level: Option<String>,
+    /// How long the title card has been shown for.
+    pub title_card: Option<f64>,
+    /// Since the level was entered or restarted.
+    pub stats: LevelStats,
+    pub summary_card: Option<SummaryCard>,
+    pub ghost: Ghost,
+    pub speedrun: SpeedrunTimer,
+    pub time_control: TimeControl,
 
     pub hard_reset_state: SlotMap<EntityKey, EntityTracker>,
 
@@ -57,18 +99,25 @@ pub struct Level {
     pub mouse_position: Point2<f64>,
 
     pub frame: FrameIndex,
+    /// How far between the last update and the next one the frame being drawn is, from 0 to 1.
+    /// Only used for drawing, so the simulation stays on its fixed timestep.
+    pub tick_fraction: f64,
     pub fade_out_frame: Option<FrameIndex>,
+    pub restart_frame: Option<FrameIndex>,
     pub entities: SlotMap<EntityKey, EntityTracker>,
+    /// What every entity's `evaluate` returned on the last update, for the debug overlay.
+    pub entity_outputs: SecondaryMap<EntityKey, bool>,
     pub input_readers: Vec<EntityKey>,
+    /// Polled once per update, such as gamepads.
+    pub input_sources: Vec<Box<dyn InputSource>>,
 
-    pub texture_atlas: Texture2D,
-    pub default_texture: Camera2D,
-
-    pub mask_texture: Camera2D,
-    pub mask_material: Material,
-
-    pub wall_texture: Camera2D,
-    pub wall_mask_material: Material,
+    /// Created on the first draw, so levels can be simulated without a window.
+    pub graphics: Option<LevelGraphics>,
+    /// Set by hard resets, so that everything the player has explored is forgotten on the next draw.
+    pub forget_explored: bool,
+    /// Game updates since the last draw, so that areas are remembered at the same speed no matter
+    /// the frame rate.
+    pub updates_since_draw: u32,
 
     pub tile_grid: TileGrid<Option<Tile>>,
     pub light_grid: LightGrid,
@@ -85,20 +134,176 @@ pub struct Level {
 
     pub occlude_wall_shadows: bool,
 
+    pub timeline: Timeline,
+    pub paradox_inspector_active: bool,
+    pub debug_overlay_active: bool,
+
+    pub progress: Progress,
+    pub level_graph: Option<LevelGraph>,
+    pub level_select: LevelSelect,
+
+    pub quicksave: Option<Vec<u8>>,
+    pub notice: Option<(String, f64)>,
+
     pub the_end: Option<f64>,
 }
 
-new_key_type! {
-    pub struct EntityKey;
+/// Everything needed to draw a level that lives on the GPU.
+pub struct LevelGraphics {
+    pub texture_atlas: Texture2D,
+    pub default_texture: Camera2D,
+
+    pub mask_texture: Camera2D,
+    pub mask_material: Material,
+
+    pub wall_texture: Camera2D,
+    pub wall_mask_material: Material,
+
+    /// Everywhere the player has seen since the level was last hard reset, in white.
+    pub explored_texture: Camera2D,
+    pub explored_material: Material,
+    /// Just the tiles, shown dimmed wherever the player has explored but can't currently see.
+    pub memory_texture: Camera2D,
+    pub memory_material: Material,
+
+    /// Where lamps light the level, drawn over the mask as darkness in levels with any.
+    pub light_texture: Camera2D,
+    pub darkness_material: Material,
 }
 
-impl Level {
-    pub fn new(level: String) -> Level {
+impl LevelGraphics {
+    pub fn new() -> LevelGraphics {
         let texture_atlas = Texture2D::from_image(
             &Image::from_file_with_format(crate::TEXTURE_ATLAS, None).unwrap(),
         );
         texture_atlas.set_filter(FilterMode::Nearest);
 
+        LevelGraphics {
+            texture_atlas,
+            default_texture: Level::new_render_target(crate::screen_pixel_size()),
+
+            mask_texture: Level::new_render_target(crate::smooth_screen_pixel_size()),
+            mask_material: material::load_material(
+                ShaderSource::Glsl {
+                    vertex: DEFAULT_VERTEX_SHADER,
+                    fragment: DEFAULT_FRAGMENT_SHADER,
+                },
+                MaterialParams {
+                    pipeline_params: PipelineParams {
+                        color_write: (true, true, true, true),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+
+            wall_texture: Level::new_render_target(crate::screen_pixel_size()),
+            wall_mask_material: material::load_material(
+                ShaderSource::Glsl {
+                    vertex: DEFAULT_VERTEX_SHADER,
+                    fragment: MASK_FRAGMENT_SHADER,
+                },
+                MaterialParams {
+                    pipeline_params: PipelineParams {
+                        color_write: (true, true, true, true),
+                        ..Default::default()
+                    },
+                    textures: vec!["Memory".to_owned(), "Explored".to_owned()],
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+
+            explored_texture: Level::new_render_target(crate::screen_pixel_size()),
+            explored_material: material::load_material(
+                ShaderSource::Glsl {
+                    vertex: DEFAULT_VERTEX_SHADER,
+                    fragment: DEFAULT_FRAGMENT_SHADER,
+                },
+                MaterialParams {
+                    pipeline_params: PipelineParams {
+                        color_blend: Some(BlendState::new(
+                            Equation::Add,
+                            BlendFactor::One,
+                            BlendFactor::One,
+                        )),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+
+            memory_texture: Level::new_render_target(crate::screen_pixel_size()),
+            memory_material: material::load_material(
+                ShaderSource::Glsl {
+                    vertex: DEFAULT_VERTEX_SHADER,
+                    fragment: MEMORY_FRAGMENT_SHADER,
+                },
+                MaterialParams {
+                    pipeline_params: PipelineParams {
+                        color_write: (true, true, true, true),
+                        ..Default::default()
+                    },
+                    textures: vec!["Explored".to_owned()],
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+
+            light_texture: Level::new_render_target(crate::smooth_screen_pixel_size()),
+            darkness_material: material::load_material(
+                ShaderSource::Glsl {
+                    vertex: DEFAULT_VERTEX_SHADER,
+                    fragment: DARKNESS_FRAGMENT_SHADER,
+                },
+                MaterialParams {
+                    pipeline_params: PipelineParams {
+                        color_blend: Some(BlendState::new(
+                            Equation::Add,
+                            BlendFactor::Value(BlendValue::SourceAlpha),
+                            BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+                        )),
+                        alpha_blend: Some(BlendState::new(
+                            Equation::Add,
+                            BlendFactor::One,
+                            BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+                        )),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        }
+    }
+
+    pub fn update_render_targets(&mut self, occlude_wall_shadows: bool) {
+        Level::update_render_target(&mut self.default_texture, crate::screen_pixel_size());
+        Level::update_render_target(&mut self.mask_texture, crate::smooth_screen_pixel_size());
+        Level::update_render_target(&mut self.light_texture, crate::smooth_screen_pixel_size());
+        Level::update_render_target(&mut self.explored_texture, crate::screen_pixel_size());
+        Level::update_render_target(&mut self.memory_texture, crate::screen_pixel_size());
+        if occlude_wall_shadows {
+            Level::update_render_target(&mut self.wall_texture, crate::screen_pixel_size());
+        }
+    }
+}
+
+new_key_type! {
+    pub struct EntityKey;
+}
+
+/// A level's tile grid, initial state and metadata, as stored in its file.
+pub type DecodedLevel = (
+    TileGrid<Option<Tile>>,
+    SlotMap<EntityKey, EntityTracker>,
+    LevelMetadata,
+);
+
+impl Level {
+    pub fn new(level: String) -> Level {
         if TILE_KINDS.lock().unwrap().is_empty() {
             tile::add_tile_kind(TileKind {
                 name: "brick1".to_owned(),
@@ -120,12 +325,25 @@ impl Level {
                 pixel_kind: Pixel::None,
                 texture_location: point![1, 1],
             });
+            tile::add_tile_kind(TileKind {
+                name: "spikes".to_owned(),
+                pixel_kind: Pixel::Lethal,
+                texture_location: point![2, 0],
+            });
         }
 
         Level {
             filesystem: FileSystem::default(),
             level_name: level,
             level_data: None,
+            metadata: LevelMetadata::default(),
+            entered_level: None,
+            title_card: None,
+            stats: LevelStats::default(),
+            summary_card: None,
+            ghost: Ghost::default(),
+            speedrun: SpeedrunTimer::new(),
+            time_control: TimeControl::default(),
 
             hard_reset_state: SlotMap::default(),
 
@@ -133,44 +351,17 @@ impl Level {
             mouse_position: point![0.0, 0.0],
 
             frame: 0,
+            tick_fraction: 1.0,
             fade_out_frame: None,
+            restart_frame: None,
             entities: SlotMap::default(),
+            entity_outputs: SecondaryMap::default(),
             input_readers: Vec::new(),
+            input_sources: Vec::new(),
 
-            texture_atlas,
-            default_texture: Self::new_render_target(crate::screen_pixel_size()),
-
-            mask_texture: Self::new_render_target(crate::smooth_screen_pixel_size()),
-            mask_material: material::load_material(
-                ShaderSource::Glsl {
-                    vertex: DEFAULT_VERTEX_SHADER,
-                    fragment: DEFAULT_FRAGMENT_SHADER,
-                },
-                MaterialParams {
-                    pipeline_params: PipelineParams {
-                        color_write: (true, true, true, true),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-            )
-            .unwrap(),
-
-            wall_texture: Self::new_render_target(crate::screen_pixel_size()),
-            wall_mask_material: material::load_material(
-                ShaderSource::Glsl {
-                    vertex: DEFAULT_VERTEX_SHADER,
-                    fragment: MASK_FRAGMENT_SHADER,
-                },
-                MaterialParams {
-                    pipeline_params: PipelineParams {
-                        color_write: (true, true, true, true),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                },
-            )
-            .unwrap(),
+            graphics: None,
+            forget_explored: true,
+            updates_since_draw: 0,
 
             tile_grid: TileGrid::default(),
             light_grid: LightGrid::default(),
@@ -187,6 +378,17 @@ impl Level {
 
             occlude_wall_shadows: true,
 
+            timeline: Timeline::default(),
+            paradox_inspector_active: false,
+            debug_overlay_active: false,
+
+            progress: Progress::load(),
+            level_graph: None,
+            level_select: LevelSelect::default(),
+
+            quicksave: None,
+            notice: None,
+
             the_end: None,
         }
     }
@@ -199,6 +401,19 @@ impl Level {
 
         level.append(&mut bincode::serde::encode_to_vec(&self.hard_reset_state, config).unwrap());
 
+        let metadata = LevelMetadata {
+            view_radii: self
+                .hard_reset_state
+                .iter()
+                .filter_map(|(key, entity)| Some((key, entity.inner.as_player()?.view_radius?)))
+                .collect(),
+            ..self.metadata.clone()
+        };
+
+        if metadata != LevelMetadata::default() {
+            level.append(&mut bincode::serde::encode_to_vec(&metadata, config).unwrap());
+        }
+
         level
     }
 
@@ -217,16 +432,19 @@ impl Level {
             self.level_data.as_ref().unwrap()
         };
 
-        let (tile_grid, read) =
-            bincode::serde::decode_from_slice(data, bincode::config::standard()).unwrap();
-
-        let data = &data[read..];
+        let (tile_grid, initial_state, metadata) = match Self::decode_level(data) {
+            Ok(level) => level,
+            Err(error) => {
+                // Otherwise it would be used again when loading a different level.
+                self.level_data = None;
 
-        let (initial_state, _) =
-            bincode::serde::decode_from_slice(data, bincode::config::standard()).unwrap();
+                return Err(error);
+            }
+        };
 
         self.hard_reset_state = initial_state;
         self.tile_grid = tile_grid;
+        self.metadata = metadata;
         self.light_grid = LightGrid::default();
 
         let bounds = self.tile_grid.bounds();
@@ -245,6 +463,41 @@ impl Level {
         Ok(())
     }
 
+    /// Splits level data produced by `save` back into its tile grid, initial state and metadata.
+    pub fn decode_level(data: &[u8]) -> Result<DecodedLevel, LoadLevelError> {
+        let (tile_grid, read) =
+            bincode::serde::decode_from_slice(data, bincode::config::standard())
+                .map_err(LoadLevelError::DecodeError)?;
+
+        let data = &data[read..];
+
+        let (mut initial_state, read): (SlotMap<EntityKey, EntityTracker>, _) =
+            bincode::serde::decode_from_slice(data, bincode::config::standard())
+                .map_err(LoadLevelError::DecodeError)?;
+
+        let data = &data[read..];
+
+        // Levels without metadata end here.
+        let mut metadata: LevelMetadata = if data.is_empty() {
+            LevelMetadata::default()
+        } else {
+            bincode::serde::decode_from_slice(data, bincode::config::standard())
+                .map_err(LoadLevelError::DecodeError)?
+                .0
+        };
+
+        for (key, radius) in mem::take(&mut metadata.view_radii) {
+            if let Some(player) = initial_state
+                .get_mut(key)
+                .and_then(|entity| entity.inner.as_player_mut())
+            {
+                player.view_radius = Some(radius);
+            }
+        }
+
+        Ok((tile_grid, initial_state, metadata))
+    }
+
     pub fn set_tile(&mut self, index: TileIndex, tile: Option<Tile>) {
         if self.tile_grid[index] != tile {
             self.tile_grid[index] = tile;
@@ -278,12 +531,23 @@ impl Level {
 
         self.soft_reset_state = Self::entities_from_initial_state(&self.hard_reset_state);
 
+        // The light grid was just rebuilt, so there's nothing left for these to clean up.
+        self.entities.clear();
+        self.entity_outputs.clear();
         self.load_initial_entities();
 
+        self.forget_explored = true;
+
+        self.enter_level();
+
         Ok(())
     }
 
     pub fn load_initial_entities(&mut self) {
+        for (_, entity) in &mut self.entities {
+            entity.inner.despawn(&mut self.light_grid);
+        }
+
         self.entities.clone_from(&self.soft_reset_state);
         self.input_readers.clear();
 
@@ -295,6 +559,7 @@ impl Level {
 
         self.frame = 0;
         self.fade_out_frame = None;
+        self.restart_frame = None;
     }
 
     pub fn step_at_level_start(&mut self) {
@@ -303,13 +568,69 @@ impl Level {
     }
 
     pub fn update(&mut self) {
+        for entity in self.entities.values_mut() {
+            entity.previous_position = Some(entity.inner.position());
+        }
+
+        self.update_notice();
+        self.update_title_card();
+
         if self.level_editor_active {
             self.update_level_editor();
-        } else {
+        } else if !self.level_select.active {
+            self.poll_input_sources();
             self.update_game();
+            self.updates_since_draw += 1;
+            self.record_ghost_frame();
+            self.update_stats();
+            self.update_speedrun();
+        }
+    }
+
+    /// Gives every player, including those waiting in the reset states, inputs for the current
+    /// key bindings.
+    pub fn apply_key_bindings(&mut self) {
+        let entities = self
+            .entities
+            .values_mut()
+            .chain(self.soft_reset_state.values_mut())
+            .chain(self.hard_reset_state.values_mut());
+
+        for entity in entities {
+            if let Some(player) = entity.inner.as_player_mut() {
+                player.motion_input = DirectionalInput::default();
+                player.rotate_input = player::rotate_input();
+            }
         }
     }
 
+    pub fn add_input_source(&mut self, source: impl InputSource + 'static) {
+        self.input_sources.push(Box::new(source));
+    }
+
+    pub fn poll_input_sources(&mut self) {
+        if self.input_sources.is_empty() {
+            return;
+        }
+
+        let input = self
+            .input_sources
+            .iter_mut()
+            .fold(AnalogInput::default(), |input, source| {
+                input.combine(source.poll())
+            });
+
+        self.input_readers.retain(|&key| {
+            let Some(entity) = self.entities.get_mut(key) else {
+                return false;
+            };
+
+            entity.analog_input(input);
+
+            true
+        });
+    }
+
     pub fn update_game(&mut self) {
         let mut actions = Vec::new();
 
@@ -367,6 +688,8 @@ impl Level {
             updates.insert(key, result);
         }
 
+        self.entity_outputs = updates;
+
         self.entities.retain(|_, entity| !entity.inner.is_empty());
 
         self.frame = self
@@ -374,6 +697,13 @@ impl Level {
             .checked_add(1)
             .expect("Game should not run for a galactically long time.");
 
+        if self
+            .restart_frame
+            .is_some_and(|restart_frame| self.frame >= restart_frame)
+        {
+            actions.push(GameAction::RestartLoop);
+        }
+
         if let Some(action) = actions.iter().max() {
             let old_level_name = self.level_name.clone();
 
@@ -402,8 +732,19 @@ impl Level {
                     self.fade_out_frame = Some(self.frame + 16);
                 }
             }
+            GameAction::PlayerDied => {
+                if self.restart_frame.is_none() {
+                    self.restart_frame = Some(self.frame + RESTART_DELAY);
+                    self.stats.deaths += 1;
+                }
+            }
+            GameAction::RestartLoop => {
+                self.load_initial_entities();
+                self.step_at_level_start();
+            }
             GameAction::HardReset => {
                 self.reset()?;
+                self.reset_stats();
                 self.step_at_level_start();
             }
             GameAction::HardResetKeepPlayer => {
@@ -439,6 +780,7 @@ impl Level {
                 self.step_at_level_start();
             }
             GameAction::SoftReset => {
+                self.stats.loops += 1;
                 self.load_initial_entities();
                 self.step_at_level_start();
             }
@@ -484,14 +826,38 @@ impl Level {
                 }
             }
             GameAction::LoadLevel(path) => {
-                self.level_name.clone_from(path);
+                let completed_title = self.display_title().to_owned();
+                let completed_stats = self.stats;
+                let completed_ghost = self.take_ghost_recording();
+
+                let completed_level = mem::replace(&mut self.level_name, path.clone());
                 self.level_data = None;
 
                 self.reset()?;
                 self.step_at_level_start();
+
+                self.record_split(completed_level.clone());
+                self.save_ghost_if_best(&completed_level, completed_ghost, completed_stats);
+                self.record_level_stats(completed_level.clone(), completed_title, completed_stats);
+                self.record_level_completed(completed_level);
             }
             GameAction::StartEndSequence => {
                 self.the_end = Some(0.0);
+
+                self.finish_speedrun();
+
+                let recording = self.take_ghost_recording();
+                self.save_ghost_if_best(&self.level_name.clone(), recording, self.stats);
+
+                self.record_level_stats(
+                    self.level_name.clone(),
+                    self.display_title().to_owned(),
+                    self.stats,
+                );
+                self.progress
+                    .completed_levels
+                    .insert(self.level_name.clone());
+                self.save_progress();
             }
         }
 
@@ -507,18 +873,23 @@ impl Level {
     }
 
     pub fn draw_game(&mut self) {
-        Self::update_render_target(&mut self.default_texture, crate::screen_pixel_size());
-        Self::update_render_target(&mut self.mask_texture, crate::smooth_screen_pixel_size());
-        if self.occlude_wall_shadows {
-            Self::update_render_target(&mut self.wall_texture, crate::screen_pixel_size());
-        }
+        let positions = self.interpolated_positions();
+
+        self.graphics
+            .get_or_insert_with(LevelGraphics::new)
+            .update_render_targets(self.occlude_wall_shadows);
+        let graphics = self.graphics.as_ref().unwrap();
 
         // Trace vision
         let view_areas = self
             .entities
             .iter()
-            .map(|(_, entity)| Some((entity.inner.view_area()?, entity.inner.view_kind()?)))
-            .flatten()
+            .filter_map(|(key, entity)| {
+                let mut area = entity.inner.view_area()?;
+                area.origin += Self::interpolation_offset(&positions, key, entity);
+
+                Some((area, entity.inner.view_kind()?))
+            })
             .collect::<Vec<_>>();
 
         let past_visibility = if let Some(fade_out_frame) = self.fade_out_frame {
@@ -532,119 +903,91 @@ impl Level {
         let screen_rect = crate::screen_rect();
 
         camera::push_camera_state();
-        camera::set_camera(&self.default_texture);
+        camera::set_camera(&graphics.default_texture);
 
         window::clear_background(colors::BLACK);
 
         // Non-wall Tiles
-        {
-            let tile_kinds = tile::TILE_KINDS.lock().unwrap();
-
-            let bounds = self.tile_grid.bounds();
-            for x in bounds.left()..bounds.right() + 1 {
-                for y in bounds.top()..bounds.bottom() + 1 {
-                    let Some(tile) = self.tile_grid[point![x, y]] else {
-                        continue;
-                    };
-
-                    let kind = &tile_kinds[tile.kind];
-
-                    if kind.pixel_kind.blocks_light() {
-                        continue;
-                    }
-
-                    texture::draw_texture_ex(
-                        &self.texture_atlas,
-                        x as f32 * TILE_SIZE as f32,
-                        y as f32 * TILE_SIZE as f32,
-                        colors::WHITE,
-                        DrawTextureParams {
-                            source: Some(kind.texture_rect()),
-                            ..Default::default()
-                        },
-                    );
-                }
-            }
-        }
+        self.draw_tiles(&graphics.texture_atlas, false);
 
         // Floor like entities
-        for (_, entity) in &mut self.entities {
-            entity.inner.draw_floor(&self.texture_atlas);
-        }
+        Self::draw_entities(&mut self.entities, &positions, |entity| {
+            entity.draw_floor(&graphics.texture_atlas)
+        });
 
         if self.occlude_wall_shadows {
             camera::push_camera_state();
-            camera::set_camera(&self.wall_texture);
+            camera::set_camera(&graphics.wall_texture);
             window::clear_background(colors::BLANK);
         }
 
         // Wall Tiles
-        {
-            let tile_kinds = tile::TILE_KINDS.lock().unwrap();
-
-            let bounds = self.tile_grid.bounds();
-            for x in bounds.left()..bounds.right() + 1 {
-                for y in bounds.top()..bounds.bottom() + 1 {
-                    let Some(tile) = self.tile_grid[point![x, y]] else {
-                        continue;
-                    };
-
-                    let kind = &tile_kinds[tile.kind];
-
-                    if !kind.pixel_kind.blocks_light() {
-                        continue;
-                    }
-
-                    texture::draw_texture_ex(
-                        &self.texture_atlas,
-                        x as f32 * TILE_SIZE as f32,
-                        y as f32 * TILE_SIZE as f32,
-                        colors::WHITE,
-                        DrawTextureParams {
-                            source: Some(kind.texture_rect()),
-                            ..Default::default()
-                        },
-                    );
-                }
-            }
-        }
+        self.draw_tiles(&graphics.texture_atlas, true);
 
         // Wall like entities
-        for (_, entity) in &mut self.entities {
-            entity.inner.draw_wall(&self.texture_atlas);
-        }
+        Self::draw_entities(&mut self.entities, &positions, |entity| {
+            entity.draw_wall(&graphics.texture_atlas)
+        });
 
         if self.occlude_wall_shadows {
-            camera::set_camera(&self.default_texture);
+            camera::set_camera(&graphics.default_texture);
 
-            texture::draw_texture_ex(
-                &self.wall_texture.render_target.as_ref().unwrap().texture,
-                screen_rect.x + screen_rect.w / 2.0 - screen_rect.h,
-                screen_rect.y,
-                colors::WHITE,
-                DrawTextureParams {
-                    dest_size: Some([screen_rect.h * 2.0, screen_rect.h].into()),
-                    ..Default::default()
-                },
-            );
+            Self::draw_level_texture(&graphics.wall_texture, colors::WHITE);
             camera::pop_camera_state();
         }
 
         // Vision occluded entities
-        for (_, entity) in &mut self.entities {
-            entity.inner.draw_back(&self.texture_atlas);
-        }
+        Self::draw_entities(&mut self.entities, &positions, |entity| {
+            entity.draw_back(&graphics.texture_atlas)
+        });
 
-        Self::draw_pixel_perfect_camera(&self.default_texture);
+        Self::draw_pixel_perfect_camera(&graphics.default_texture);
 
         camera::pop_camera_state();
 
-        // Vision mask
+        // Explored memory
         camera::push_camera_state();
-        camera::set_camera(&self.mask_texture);
+        camera::set_camera(&graphics.explored_texture);
+
+        if self.forget_explored {
+            window::clear_background(colors::BLACK);
+            self.forget_explored = false;
+        }
+
+        // Blended additively once per update, so faded edges are only remembered after being seen
+        // for a while.
+        material::gl_use_material(&graphics.explored_material);
+
+        for _ in 0..mem::take(&mut self.updates_since_draw) {
+            for (area, kind) in &view_areas {
+                if let ViewKind::Present { .. } = kind {
+                    area.draw_wall_lighting(colors::WHITE);
+                    area.draw_direct_lighting(colors::WHITE);
+                }
+            }
+        }
+
+        material::gl_use_default_material();
+        camera::set_camera(&graphics.memory_texture);
         window::clear_background(colors::BLACK);
 
-        material::gl_use_material(&self.mask_material);
+        self.draw_tiles(&graphics.texture_atlas, false);
+        self.draw_tiles(&graphics.texture_atlas, true);
+
+        // Vision mask
+        camera::set_camera(&graphics.mask_texture);
+        window::clear_background(colors::BLACK);
+
+        let memory_color = Color::new(MEMORY_BRIGHTNESS, MEMORY_BRIGHTNESS, MEMORY_BRIGHTNESS, 1.0);
+
+        graphics
+            .memory_material
+            .set_texture("Explored", Self::render_texture(&graphics.explored_texture));
+        material::gl_use_material(&graphics.memory_material);
+
+        Self::draw_level_texture(&graphics.memory_texture, memory_color);
+
+        material::gl_use_material(&graphics.mask_material);
 
         let mut indecies = (0..view_areas.len()).collect::<Vec<_>>();
         indecies.sort_unstable_by(|&a, &b| {
@@ -654,66 +997,74 @@ impl Level {
                 .total_cmp(&view_areas[b].1.confusion())
         });
 
+        let mask_color = |kind| match kind {
+            ViewKind::Present { confusion } => {
+                let show = (confusion as f32 / 0.1).clamp(0.0, 1.0);
+                Color::new(show, confusion as f32, 0.0, show * 0.2)
+            }
+            ViewKind::Past { confusion } => Color::new(
+                past_visibility,
+                past_visibility * confusion as f32,
+                0.0,
+                1.0 - 0.8 * past_visibility,
+            ),
+        };
+
         for &i in &indecies {
             let &(ref area, kind) = &view_areas[i];
-
-            match kind {
-                ViewKind::Present { confusion } => {
-                    let show = (confusion as f32 / 0.1).clamp(0.0, 1.0);
-                    area.draw_wall_lighting(Color::new(show, confusion as f32, 0.0, show * 0.2));
-                }
-                ViewKind::Past { confusion } => {
-                    area.draw_wall_lighting(Color::new(
-                        past_visibility,
-                        past_visibility * confusion as f32,
-                        0.0,
-                        1.0 - 0.8 * past_visibility,
-                    ));
-                }
-            }
+            area.draw_wall_lighting(mask_color(kind));
         }
 
         if self.occlude_wall_shadows {
-            material::gl_use_material(&self.wall_mask_material);
+            graphics
+                .wall_mask_material
+                .set_texture("Memory", Self::render_texture(&graphics.memory_texture));
+            graphics
+                .wall_mask_material
+                .set_texture("Explored", Self::render_texture(&graphics.explored_texture));
+            material::gl_use_material(&graphics.wall_mask_material);
 
-            texture::draw_texture_ex(
-                &self.wall_texture.render_target.as_ref().unwrap().texture,
-                screen_rect.x + screen_rect.w / 2.0 - screen_rect.h,
-                screen_rect.y,
-                colors::WHITE,
-                DrawTextureParams {
-                    dest_size: Some([screen_rect.h * 2.0, screen_rect.h].into()),
-                    ..Default::default()
-                },
-            );
+            Self::draw_level_texture(&graphics.wall_texture, memory_color);
 
-            material::gl_use_material(&self.mask_material);
+            material::gl_use_material(&graphics.mask_material);
         }
 
         for &i in &indecies {
             let &(ref area, kind) = &view_areas[i];
+            area.draw_direct_lighting(mask_color(kind));
+        }
 
-            match kind {
-                ViewKind::Present { confusion } => {
-                    let show = (confusion as f32 / 0.1).clamp(0.0, 1.0);
-                    area.draw_direct_lighting(Color::new(show, confusion as f32, 0.0, show * 0.2));
-                }
-                ViewKind::Past { confusion } => {
-                    area.draw_direct_lighting(Color::new(
-                        past_visibility,
-                        past_visibility * confusion as f32,
-                        0.0,
-                        1.0 - 0.8 * past_visibility,
-                    ));
-                }
-            }
+        // The mask isn't blended, so faded edges cover up any areas drawn before them.
+        for &i in &indecies {
+            let &(ref area, kind) = &view_areas[i];
+            area.draw_unfaded_lighting(mask_color(kind));
+        }
+
+        if lamp::lit_areas(self.entities.values()).is_some() {
+            let lit_areas = self
+                .entities
+                .iter()
+                .filter_map(|(key, entity)| {
+                    let mut area = entity.inner.light_area()?.clone();
+                    area.origin += Self::interpolation_offset(&positions, key, entity);
+
+                    Some(area)
+                })
+                .collect::<Vec<_>>();
+
+            Self::draw_darkness(graphics, &lit_areas, &view_areas);
         }
 
         material::gl_use_default_material();
         camera::set_default_camera();
 
         texture::draw_texture_ex(
-            &self.mask_texture.render_target.as_ref().unwrap().texture,
+            &graphics
+                .mask_texture
+                .render_target
+                .as_ref()
+                .unwrap()
+                .texture,
             0.0,
             0.0,
             colors::WHITE,
@@ -723,42 +1074,57 @@ impl Level {
             },
         );
 
-        camera::set_camera(&self.default_texture);
+        camera::set_camera(&graphics.default_texture);
 
         window::clear_background(colors::BLANK);
 
         // Always visible entities
-        for (_, entity) in &mut self.entities {
-            entity.inner.draw_effect_back(&self.texture_atlas);
-        }
+        Self::draw_entities(&mut self.entities, &positions, |entity| {
+            entity.draw_effect_back(&graphics.texture_atlas)
+        });
 
-        for (_, entity) in &mut self.entities {
-            entity.inner.draw_overlay_back(&self.texture_atlas);
-        }
+        Self::draw_entities(&mut self.entities, &positions, |entity| {
+            entity.draw_overlay_back(&graphics.texture_atlas)
+        });
 
-        Self::draw_pixel_perfect_camera(&self.default_texture);
+        Self::draw_pixel_perfect_camera(&graphics.default_texture);
 
         camera::pop_camera_state();
 
-        for (_, entity) in &mut self.entities {
-            entity.inner.draw_front(&self.texture_atlas);
-        }
+        Self::draw_entities(&mut self.entities, &positions, |entity| {
+            entity.draw_front(&graphics.texture_atlas)
+        });
+
+        self.draw_ghost();
 
         camera::push_camera_state();
-        camera::set_camera(&self.default_texture);
+        camera::set_camera(&graphics.default_texture);
 
         window::clear_background(colors::BLANK);
 
-        for (_, entity) in &mut self.entities {
-            entity.inner.draw_effect_front(&self.texture_atlas);
-        }
+        Self::draw_entities(&mut self.entities, &positions, |entity| {
+            entity.draw_effect_front(&graphics.texture_atlas)
+        });
 
-        for (_, entity) in &mut self.entities {
-            entity.inner.draw_overlay_front(&self.texture_atlas);
-        }
+        Self::draw_entities(&mut self.entities, &positions, |entity| {
+            entity.draw_overlay_front(&graphics.texture_atlas)
+        });
 
         Self::draw_wires(&self.entities, None);
 
+        if let Some(restart_frame) = self.restart_frame {
+            let progress =
+                1.0 - restart_frame.saturating_sub(self.frame) as f32 / RESTART_DELAY as f32;
+
+            shapes::draw_rectangle(
+                screen_rect.x,
+                screen_rect.y,
+                screen_rect.w,
+                screen_rect.h,
+                Color::new(0.5, 0.0, 0.0, 0.6 * progress),
+            );
+        }
+
         if let Some(time) = self.the_end {
             let texture_index = match time {
                 ..5.0 => {
@@ -787,7 +1153,7 @@ impl Level {
                 window::clear_background(background);
 
                 texture::draw_texture_ex(
-                    &self.texture_atlas,
+                    &graphics.texture_atlas,
                     -THE_END_TEXTURE_SIZE.x / 2.0,
                     -THE_END_TEXTURE_SIZE.y / 2.0,
                     foreground,
@@ -803,9 +1169,135 @@ impl Level {
             }
         }
 
-        Self::draw_pixel_perfect_camera(&self.default_texture);
+        Self::draw_pixel_perfect_camera(&graphics.default_texture);
 
         camera::pop_camera_state();
+
+        self.draw_title_card();
+        self.draw_summary_card();
+
+        if self.speedrun.overlay_active {
+            self.draw_speedrun_timer();
+        }
+
+        self.draw_time_control();
+
+        if self.timeline.active {
+            self.draw_timeline();
+        }
+
+        if self.paradox_inspector_active {
+            self.draw_paradox_inspector();
+        }
+
+        if self.debug_overlay_active {
+            self.draw_debug_overlay();
+        }
+
+        if self.level_select.active {
+            self.draw_level_select();
+        }
+    }
+
+    /// Where every entity should be drawn between updates. Only used for drawing, the entities
+    /// themselves stay where the last update left them.
+    fn interpolated_positions(&self) -> SecondaryMap<EntityKey, Point2<f64>> {
+        self.entities
+            .iter()
+            .map(|(key, entity)| (key, entity.interpolated_position(self.tick_fraction)))
+            .collect()
+    }
+
+    /// How far from where it actually is an entity should be drawn.
+    fn interpolation_offset(
+        positions: &SecondaryMap<EntityKey, Point2<f64>>,
+        key: EntityKey,
+        entity: &EntityTracker,
+    ) -> Vector2<f64> {
+        positions.get(key).map_or(vector![0.0, 0.0], |position| {
+            position - entity.inner.position()
+        })
+    }
+
+    /// Calls `draw` for every entity, with everything it draws moved to the entity's interpolated
+    /// position.
+    fn draw_entities(
+        entities: &mut SlotMap<EntityKey, EntityTracker>,
+        positions: &SecondaryMap<EntityKey, Point2<f64>>,
+        mut draw: impl FnMut(&mut dyn Entity),
+    ) {
+        for (key, entity) in entities {
+            let offset = Self::interpolation_offset(positions, key, entity);
+
+            if offset == vector![0.0, 0.0] {
+                draw(&mut *entity.inner);
+                continue;
+            }
+
+            let translation = Mat4::from_translation(vec3(offset.x as f32, offset.y as f32, 0.0));
+
+            // The context is never held on to while anything else draws.
+            unsafe { window::get_internal_gl() }
+                .quad_gl
+                .push_model_matrix(translation);
+
+            draw(&mut *entity.inner);
+
+            unsafe { window::get_internal_gl() }
+                .quad_gl
+                .pop_model_matrix();
+        }
+    }
+
+    /// Draws every tile that blocks light if `walls`, otherwise every tile that doesn't.
+    fn draw_tiles(&self, texture_atlas: &Texture2D, walls: bool) {
+        let tile_kinds = tile::TILE_KINDS.lock().unwrap();
+
+        let bounds = self.tile_grid.bounds();
+        for x in bounds.left()..bounds.right() + 1 {
+            for y in bounds.top()..bounds.bottom() + 1 {
+                let Some(tile) = self.tile_grid[point![x, y]] else {
+                    continue;
+                };
+
+                let kind = &tile_kinds[tile.kind];
+
+                if kind.pixel_kind.blocks_light() != walls {
+                    continue;
+                }
+
+                texture::draw_texture_ex(
+                    texture_atlas,
+                    x as f32 * TILE_SIZE as f32,
+                    y as f32 * TILE_SIZE as f32,
+                    colors::WHITE,
+                    DrawTextureParams {
+                        source: Some(kind.texture_rect()),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+
+    fn render_texture(camera: &Camera2D) -> Texture2D {
+        camera.render_target.as_ref().unwrap().texture.clone()
+    }
+
+    /// Draws a render target the size of `crate::screen_pixel_size` over the area it was drawn from.
+    fn draw_level_texture(camera: &Camera2D, color: Color) {
+        let screen_rect = crate::screen_rect();
+
+        texture::draw_texture_ex(
+            &camera.render_target.as_ref().unwrap().texture,
+            screen_rect.x + screen_rect.w / 2.0 - screen_rect.h,
+            screen_rect.y,
+            color,
+            DrawTextureParams {
+                dest_size: Some([screen_rect.h * 2.0, screen_rect.h].into()),
+                ..Default::default()
+            },
+        );
     }
 
     pub fn draw_pixel_perfect_camera(camera: &Camera2D) {
@@ -824,13 +1316,29 @@ impl Level {
     }
 
     pub fn draw_wires(entities: &SlotMap<EntityKey, EntityTracker>, hidden_color: Option<Color>) {
-        for (_, entity) in entities {
-            for &key in entity.inner.inputs() {
+        for (start, end, color) in Self::wires(entities, hidden_color) {
+            shapes::draw_line(
+                start.x as f32,
+                start.y as f32,
+                end.x as f32,
+                end.y as f32,
+                2.0,
+                color,
+            );
+        }
+    }
+
+    /// The start, end and color of every wire, with `hidden_color` used for inputs that don't show
+    /// their power, or skipping them if it's `None`.
+    pub fn wires(
+        entities: &SlotMap<EntityKey, EntityTracker>,
+        hidden_color: Option<Color>,
+    ) -> impl Iterator<Item = (Point2<f64>, Point2<f64>, Color)> {
+        entities.values().flat_map(move |entity| {
+            entity.inner.inputs().iter().filter_map(move |&key| {
                 let input = &entities[key];
 
-                let Some(color) = input.inner.power_color().or(hidden_color) else {
-                    continue;
-                };
+                let color = input.inner.power_color().or(hidden_color)?;
 
                 let offset = entity.inner.position() - input.inner.position();
                 let start = input.inner.position() + input.inner.offset_of_wire(offset);
@@ -838,16 +1346,59 @@ impl Level {
                 let offset = start - entity.inner.position();
                 let end = entity.inner.position() + entity.inner.offset_of_wire(offset);
 
-                shapes::draw_line(
-                    start.x as f32,
-                    start.y as f32,
-                    end.x as f32,
-                    end.y as f32,
-                    2.0,
-                    color,
-                );
-            }
+                Some((start, end, color))
+            })
+        })
+    }
+
+    /// Darkens the mask everywhere that isn't lit, or close to whoever is looking. Expects the mask
+    /// to be the current camera, and leaves it that way.
+    fn draw_darkness(
+        graphics: &LevelGraphics,
+        lit_areas: &[LightArea],
+        view_areas: &[(LightArea, ViewKind)],
+    ) {
+        camera::set_camera(&graphics.light_texture);
+        material::gl_use_default_material();
+        window::clear_background(colors::BLACK);
+
+        for area in lit_areas {
+            area.draw_direct_lighting(colors::WHITE);
+        }
+
+        for area in lit_areas {
+            area.draw_unfaded_lighting(colors::WHITE);
         }
+
+        for (area, _) in view_areas {
+            shapes::draw_circle(
+                area.origin.x as f32,
+                area.origin.y as f32,
+                Player::DARK_SIGHT_DISTANCE as f32,
+                colors::WHITE,
+            );
+        }
+
+        let screen_rect = crate::screen_rect();
+
+        camera::set_camera(&graphics.mask_texture);
+        material::gl_use_material(&graphics.darkness_material);
+
+        texture::draw_texture_ex(
+            &graphics
+                .light_texture
+                .render_target
+                .as_ref()
+                .unwrap()
+                .texture,
+            screen_rect.x,
+            screen_rect.y,
+            Color::new(0.0, 0.0, 0.0, DARKNESS),
+            DrawTextureParams {
+                dest_size: Some([screen_rect.w, screen_rect.h].into()),
+                ..Default::default()
+            },
+        );
     }
 
     pub fn new_render_target(size: Vector2<u32>) -> Camera2D {
@@ -886,6 +1437,11 @@ impl Level {
     }
 
     pub fn key_down(&mut self, input: KeyCode) {
+        if self.level_select.active && !self.level_editor_active {
+            self.level_select_key_down(input);
+            return;
+        }
+
         match input {
             KeyCode::LeftShift | KeyCode::RightShift => {
                 self.shift_held = true;
@@ -896,28 +1452,8 @@ impl Level {
             KeyCode::LeftAlt | KeyCode::RightAlt => {
                 self.alt_held = true;
             }
-            KeyCode::Key0 | KeyCode::Kp0 | KeyCode::F3
-                if self.shift_held || input == KeyCode::F3 =>
-            {
-                self.the_end = None;
-                self.level_editor_active ^= true;
-
-                if !self.level_editor_active {
-                    self.exit_level_editor();
-                    self.level_data = Some(self.save());
-
-                    match self.reset() {
-                        Ok(()) => {
-                            self.step_at_level_start();
-                        }
-                        Err(error) => {
-                            self.editor.cursor = None;
-                            self.editor
-                                .command_input
-                                .push_str(&format!(". Error: {error}"));
-                        }
-                    }
-                }
+            KeyCode::Key0 | KeyCode::Kp0 if self.shift_held => {
+                self.toggle_level_editor();
             }
             // KeyCode::Escape => {
             //     if !self.level_editor_active
@@ -931,9 +1467,15 @@ impl Level {
             _ => (),
         }
 
+        self.bound_input_down(input.into());
+
         if self.level_editor_active {
             self.level_editor_key_down(input);
         } else {
+            if self.timeline.active {
+                self.timeline_key_down(input);
+            }
+
             self.input_readers.retain(|&key| {
                 let Some(entity) = self.entities.get_mut(key) else {
                     return false;
@@ -946,6 +1488,104 @@ impl Level {
         }
     }
 
+    fn toggle_level_editor(&mut self) {
+        self.the_end = None;
+        self.level_editor_active ^= true;
+        self.speedrun.ticks = None;
+
+        if !self.level_editor_active {
+            self.exit_level_editor();
+            self.level_data = Some(self.save());
+
+            match self.reset() {
+                Ok(()) => {
+                    self.step_at_level_start();
+                }
+                Err(error) => {
+                    self.editor.cursor = None;
+                    self.editor
+                        .command_input
+                        .push_str(&format!(". Error: {error}"));
+                }
+            }
+        }
+    }
+
+    /// Handles the actions from `KeyBindings` that belong to the level.
+    fn bound_input_down(&mut self, input: Binding) {
+        let bindings = input::key_bindings().clone();
+
+        if bindings.is_bound(Action::ToggleEditor, input) {
+            self.toggle_level_editor();
+        }
+
+        if self.level_editor_active {
+            return;
+        }
+
+        if bindings.is_bound(Action::Timeline, input) {
+            self.timeline.active ^= true;
+        }
+
+        if bindings.is_bound(Action::ParadoxInspector, input) {
+            self.toggle_paradox_inspector();
+        }
+
+        if bindings.is_bound(Action::DebugOverlay, input) {
+            self.debug_overlay_active ^= true;
+        }
+
+        if bindings.is_bound(Action::LevelSelect, input) {
+            self.open_level_select();
+        }
+
+        if bindings.is_bound(Action::Quicksave, input) {
+            self.quicksave();
+        }
+
+        if bindings.is_bound(Action::Quickload, input) {
+            self.quickload();
+        }
+
+        if bindings.is_bound(Action::SpeedrunTimer, input) {
+            self.speedrun.overlay_active ^= true;
+        }
+
+        if bindings.is_bound(Action::Ghost, input) {
+            self.ghost.active ^= true;
+        }
+
+        let mut time_control = self.time_control;
+
+        if bindings.is_bound(Action::TimeSlower, input) {
+            time_control.slower();
+        }
+
+        if bindings.is_bound(Action::TimeFaster, input) {
+            time_control.faster();
+        }
+
+        if bindings.is_bound(Action::TimePause, input) {
+            time_control.paused ^= true;
+            time_control.steps = 0;
+        }
+
+        if bindings.is_bound(Action::TimeStep, input) {
+            time_control.step(1);
+        }
+
+        if time_control != self.time_control {
+            self.set_time_control(time_control);
+            self.show_notice(self.time_control_text());
+        }
+
+        if bindings.is_bound(Action::Restart, input)
+            && let Err(error) = self.evaluate_game_action(&GameAction::HardReset)
+        {
+            self.show_notice(format!("Couldn't restart: {error}"));
+        }
+    }
+
     pub fn key_up(&mut self, input: KeyCode) {
         match input {
             KeyCode::LeftShift | KeyCode::RightShift => {
@@ -989,6 +1629,8 @@ impl Level {
             _ => (),
         }
 
+        self.bound_input_down(input.into());
+
         if self.level_editor_active {
             self.level_editor_mouse_down(input, position);
         } else {
@@ -1084,16 +1726,55 @@ pub const DEFAULT_FRAGMENT_SHADER: &str = r#"
     }
 "#;
 
+/// How dark the parts of a view that aren't lit are, in levels with lamps.
+pub const DARKNESS: f32 = 0.6;
+
+/// Draws `color` where the light texture is dark, fading out where it's lit.
+pub const DARKNESS_FRAGMENT_SHADER: &str = r#"
+    #version 100
+    varying lowp vec4 color;
+    varying lowp vec2 uv;
+
+    uniform sampler2D Texture;
+
+    void main() {
+        gl_FragColor = vec4(color.rgb, color.a * (1.0 - texture2D(Texture, uv).r));
+    }
+"#;
+
+/// How bright explored areas the player can't currently see are.
+pub const MEMORY_BRIGHTNESS: f32 = 0.35;
+
+/// Draws the remembered tiles tinted by `color` wherever they've been explored, and black
+/// everywhere else.
+pub const MEMORY_FRAGMENT_SHADER: &str = r#"
+    #version 100
+    varying lowp vec4 color;
+    varying lowp vec2 uv;
+
+    uniform sampler2D Texture;
+    uniform sampler2D Explored;
+
+    void main() {
+        gl_FragColor = vec4(color.rgb * texture2D(Texture, uv).rgb * texture2D(Explored, uv).r, 1.0);
+    }
+"#;
+
+/// Covers everything but the walls with what's remembered of them, the same as
+/// `MEMORY_FRAGMENT_SHADER`.
 pub const MASK_FRAGMENT_SHADER: &str = r#"
     #version 100
     varying lowp vec4 color;
     varying lowp vec2 uv;
 
     uniform sampler2D Texture;
+    uniform sampler2D Memory;
+    uniform sampler2D Explored;
 
     void main() {
         if (texture2D(Texture, uv).a == 0.0) {
-            gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+            lowp vec3 memory = texture2D(Memory, uv).rgb * texture2D(Explored, uv).r;
+            gl_FragColor = vec4(color.rgb * memory, 1.0);
         } else {
             discard;
         }
@@ -0,0 +1,114 @@
+use macroquad::{
+    color::{Color, colors},
+    shapes, text,
+};
+use nalgebra::point;
+
+use crate::level::{
+    EntityKey, Level,
+    entity_tracker::EntityTracker,
+    hud::{TEXT_LINE_HEIGHT, draw_text_line},
+};
+
+pub const COLLISION_RECT_COLOR: Color = Color::new(0.2, 1.0, 0.4, 0.8);
+pub const POWERED_COLOR: Color = colors::YELLOW;
+
+impl Level {
+    /// Lists the state of every entity down the right side of the screen, and outlines where each
+    /// one collides, so bugs can be tracked down while playing.
+    pub fn draw_debug_overlay(&self) {
+        let screen_rect = crate::screen_rect();
+        let right = screen_rect.x + screen_rect.w - 2.0;
+        let mut y = screen_rect.y + 8.0 + TEXT_LINE_HEIGHT * 2.0;
+
+        let mut draw_line = |line: &str, color| {
+            let width = text::measure_text(line, None, 8, 1.0).width;
+
+            draw_text_line(line, point![right - width, y], color);
+            y += TEXT_LINE_HEIGHT;
+        };
+
+        draw_line(
+            &format!(
+                "frame {} fade out {} entities {}",
+                self.frame,
+                self.fade_out_frame
+                    .map_or("none".to_string(), |frame| frame.to_string()),
+                self.entities.len(),
+            ),
+            colors::WHITE,
+        );
+
+        for (key, entity) in &self.entities {
+            let powered = self.entity_outputs.get(key).copied();
+            let color = if powered == Some(true) {
+                POWERED_COLOR
+            } else {
+                colors::LIGHTGRAY
+            };
+
+            draw_line(&self.describe_entity(key, entity), color);
+
+            self.draw_entity_bounds(key, entity);
+        }
+    }
+
+    fn describe_entity(&self, key: EntityKey, entity: &EntityTracker) -> String {
+        let position = entity.inner.position();
+
+        let collision_rect = entity
+            .inner
+            .collision_rect()
+            .map_or("none".to_string(), |rect| {
+                format!(
+                    "{}, {} {}x{}",
+                    rect.origin.x, rect.origin.y, rect.size.x, rect.size.y
+                )
+            });
+
+        let powered = match self.entity_outputs.get(key) {
+            Some(true) => "on",
+            Some(false) => "off",
+            None => "?",
+        };
+
+        let mut line = format!(
+            "{key:?} {} ({:.1}, {:.1}) rect {collision_rect} power {powered}",
+            entity.inner.typetag_name(),
+            position.x,
+            position.y,
+        );
+
+        if let Some(elevator) = entity.inner.as_elevator() {
+            line += &format!(" {:?}", elevator.state);
+        }
+
+        if let Some(player) = entity.inner.as_player() {
+            line += &format!(
+                " {:?} history {} bytes",
+                player.state,
+                player.history.size()
+            );
+        }
+
+        line
+    }
+
+    /// Outlines the pixels the entity collides with, and labels it with its key.
+    fn draw_entity_bounds(&self, key: EntityKey, entity: &EntityTracker) {
+        if let Some(rect) = entity.inner.collision_rect() {
+            shapes::draw_rectangle_lines(
+                rect.origin.x as f32,
+                rect.origin.y as f32,
+                rect.size.x as f32,
+                rect.size.y as f32,
+                1.0,
+                COLLISION_RECT_COLOR,
+            );
+        }
+
+        let position = entity.inner.position().map(|x| x as f32);
+
+        draw_text_line(&format!("{key:?}"), position, colors::WHITE);
+    }
+}
@@ -5,6 +5,7 @@ use slotmap::SlotMap;
 
 use crate::{
     collections::{history::FrameIndex, slot_guard::GuardedSlotMap},
+    input::AnalogInput,
     level::{
         EntityKey,
         entity_tracker::entity::{Entity, GameAction, empty::Empty},
@@ -17,6 +18,10 @@ pub(crate) mod entity;
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct EntityTracker {
     pub inner: Box<dyn Entity>,
+
+    /// Where the entity was before the last update, so it can be drawn in between updates.
+    #[serde(skip)]
+    pub previous_position: Option<Point2<f64>>,
 }
 
 impl Default for EntityTracker {
@@ -27,7 +32,28 @@ impl Default for EntityTracker {
 
 impl EntityTracker {
     pub fn new(inner: Box<dyn Entity>) -> Self {
-        EntityTracker { inner: inner }
+        EntityTracker {
+            inner: inner,
+            previous_position: None,
+        }
+    }
+
+    /// Where the entity should be drawn `fraction` of the way from its previous position to its
+    /// current one. Entities that moved too far in one update are treated as having teleported.
+    pub fn interpolated_position(&self, fraction: f64) -> Point2<f64> {
+        const MAX_INTERPOLATED_DISTANCE: f64 = 8.0;
+
+        let position = self.inner.position();
+
+        match self.previous_position {
+            Some(previous)
+                if (position - previous).magnitude_squared()
+                    <= MAX_INTERPOLATED_DISTANCE.powi(2) =>
+            {
+                previous + (position - previous) * fraction
+            }
+            _ => position,
+        }
     }
 
     #[must_use]
@@ -71,4 +97,10 @@ impl EntityTracker {
             self.inner.mouse_moved(position, delta);
         }
     }
+
+    pub fn analog_input(&mut self, input: AnalogInput) {
+        if self.inner.should_recieve_inputs() {
+            self.inner.analog_input(input);
+        }
+    }
 }
@@ -1,32 +1,42 @@
-use std::fmt::Debug;
+use std::{cmp::Ordering, fmt::Debug};
 
+use bincode::error::DecodeError;
 use macroquad::{
     color::Color,
     input::{KeyCode, MouseButton},
+    math::Rect,
     texture::Texture2D,
 };
 use nalgebra::{Point2, Vector2, vector};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use slotmap::SlotMap;
 
 use crate::{
     collections::{history::FrameIndex, slot_guard::GuardedSlotMap, tile_grid::TileRect},
+    input::AnalogInput,
     level::{
         EntityKey,
         entity_tracker::{
             EntityTracker,
-            entity::{elevator::Elevator, elevator_door::ElevatorDoor, player::Player},
+            entity::{
+                elevator::Elevator, elevator_door::ElevatorDoor, player::Player,
+                pushable_crate::Crate,
+            },
         },
         light_grid::{LightArea, LightGrid},
     },
+    overview::Canvas,
 };
 
 pub(crate) mod button;
 pub(crate) mod elevator;
 pub(crate) mod elevator_door;
 pub(crate) mod empty;
+pub(crate) mod lamp;
+pub(crate) mod laser;
 pub(crate) mod logic_gate;
 pub(crate) mod player;
+pub(crate) mod pushable_crate;
 
 #[typetag::serde(tag = "type")]
 pub trait Entity: 'static + Debug {
@@ -74,6 +84,9 @@ pub trait Entity: 'static + Debug {
     /// Not occluded by light. Drawn just in front of `draw_effect_front`.
     fn draw_overlay_front(&mut self, _texture_atlas: &Texture2D) {}
 
+    /// Draws the entity into a level overview, which is drawn on the CPU without a window.
+    fn draw_overview(&self, _canvas: &mut Canvas) {}
+
     /// The set of tiles an entity would collide with, if applicable.
     fn collision_rect(&self) -> Option<TileRect> {
         None
@@ -89,6 +102,17 @@ pub trait Entity: 'static + Debug {
         None
     }
 
+    /// The area lit by this entity, if applicable.
+    fn light_area(&self) -> Option<&LightArea> {
+        None
+    }
+
+    /// Whether this entity can light up the level. Levels with any are dark outside of the areas
+    /// they light, even while all of them are off.
+    fn is_light_source(&self) -> bool {
+        false
+    }
+
     /// Check if this entity is within a certain field of view, if applicable.
     fn is_within_view_area(&self, _light_grid: &LightGrid, _area: &LightArea) -> bool {
         false
@@ -99,6 +123,11 @@ pub trait Entity: 'static + Debug {
         None
     }
 
+    /// Check if anything occupying `rect` should be killed by this entity.
+    fn is_lethal_to(&self, _rect: Rect) -> bool {
+        false
+    }
+
     fn position(&self) -> Point2<f64>;
 
     fn position_mut(&mut self) -> Option<&mut Point2<f64>> {
@@ -120,6 +149,21 @@ pub trait Entity: 'static + Debug {
     /// For the duration of this call, `entities[key]` is the `Empty` entity.
     fn spawn(&mut self, _key: EntityKey, _entities: &mut SlotMap<EntityKey, EntityTracker>) {}
 
+    /// Called just before this entity is removed from the level without the light grid being
+    /// rebuilt, e.g. by a soft reset. Best used to clear any pixels this entity wrote to it.
+    fn despawn(&mut self, _light_grid: &mut LightGrid) {}
+
+    /// Encodes any state that is skipped when saving the level, so that save states can restore
+    /// it with `load_runtime_state`.
+    fn save_runtime_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Restores state encoded by `save_runtime_state`.
+    fn load_runtime_state(&mut self, _data: &[u8]) -> Result<(), DecodeError> {
+        Ok(())
+    }
+
     /// If this returns true, the elevator that this entity is destined for will break, if any.
     fn is_dead(&self) -> bool {
         false
@@ -145,6 +189,10 @@ pub trait Entity: 'static + Debug {
     /// `should_recieve_inputs` must return true for inputs to be passed through to this.
     fn mouse_moved(&mut self, _position: Point2<f64>, _delta: Vector2<f64>) {}
 
+    /// Called before every update with the combined input of the level's `InputSource`s.
+    /// `should_recieve_inputs` must return true for inputs to be passed through to this.
+    fn analog_input(&mut self, _input: AnalogInput) {}
+
     fn inputs(&self) -> &[EntityKey] {
         &[]
     }
@@ -216,6 +264,20 @@ pub trait Entity: 'static + Debug {
         None
     }
 
+    /// If this entity is a `Crate`, return Some(self).
+    ///
+    /// This should only be overridden by something which is or contains a `Crate`.
+    fn as_crate(&self) -> Option<&Crate> {
+        None
+    }
+
+    /// If this entity is a `Crate`, return Some(self).
+    ///
+    /// This should only be overridden by something which is or contains a `Crate`.
+    fn as_crate_mut(&mut self) -> Option<&mut Crate> {
+        None
+    }
+
     /// If this entity is an `Elevator`, return true.
     ///
     /// This should only be overridden by something which is or contains an `Empty`.
@@ -230,7 +292,9 @@ impl Clone for Box<dyn Entity> {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+/// Variants are stored in level files by index, so new ones must be added at the end. The order
+/// they are evaluated in is decided by `GameAction::priority` instead.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum GameAction {
     StartFadeOut,
     SoftReset,
@@ -239,6 +303,41 @@ pub enum GameAction {
     LoadLevel(String),
     SoftResetInverse,
     StartEndSequence,
+    PlayerDied,
+    RestartLoop,
+}
+
+impl GameAction {
+    /// When several actions happen on the same frame, only the one with the highest priority is
+    /// evaluated.
+    pub fn priority(&self) -> u8 {
+        match self {
+            GameAction::StartFadeOut => 0,
+            GameAction::PlayerDied => 1,
+            GameAction::RestartLoop => 2,
+            GameAction::SoftReset => 3,
+            GameAction::HardResetKeepPlayer => 4,
+            GameAction::HardReset => 5,
+            GameAction::LoadLevel(_) => 6,
+            GameAction::SoftResetInverse => 7,
+            GameAction::StartEndSequence => 8,
+        }
+    }
+}
+
+impl PartialOrd for GameAction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GameAction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (GameAction::LoadLevel(a), GameAction::LoadLevel(b)) => a.cmp(b),
+            _ => self.priority().cmp(&other.priority()),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -262,7 +361,7 @@ impl ViewKind {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
 pub struct EntityVisibleState {
     pub position: Point2<f32>,
     pub extra: u64,
@@ -280,3 +379,11 @@ impl EntityVisibleState {
         self.position.map(|x| x as f64)
     }
 }
+
+pub fn encode_runtime_state(state: &impl Serialize) -> Vec<u8> {
+    bincode::serde::encode_to_vec(state, bincode::config::standard()).unwrap()
+}
+
+pub fn decode_runtime_state<T: DeserializeOwned>(data: &[u8]) -> Result<T, DecodeError> {
+    Ok(bincode::serde::decode_from_slice(data, bincode::config::standard())?.0)
+}
@@ -1,3 +1,4 @@
+use bincode::error::DecodeError;
 use macroquad::{
     color::colors,
     math::Rect,
@@ -13,10 +14,11 @@ use crate::{
         EntityKey,
         entity_tracker::{
             EntityTracker,
-            entity::{Entity, GameAction},
+            entity::{Entity, GameAction, decode_runtime_state, encode_runtime_state},
         },
         light_grid::LightGrid,
     },
+    overview::Canvas,
 };
 
 pub const BUTTON_SIZE: Vector2<f64> = vector![8.0, 8.0];
@@ -103,6 +105,35 @@ impl Entity for Button {
         );
     }
 
+    fn draw_overview(&self, canvas: &mut Canvas) {
+        // Overviews show the level as it starts, so buttons are always drawn unpressed.
+        canvas.draw_texture_ex(
+            self.position.x as f32 - BUTTON_TEXTURE_SIZE.x / 2.0,
+            self.position.y as f32 - BUTTON_TEXTURE_SIZE.y / 2.0,
+            colors::WHITE,
+            DrawTextureParams {
+                source: Some(crate::new_texture_rect(
+                    BUTTON_TEXTURE_POSITION,
+                    BUTTON_TEXTURE_SIZE,
+                )),
+                ..Default::default()
+            },
+        );
+
+        canvas.draw_texture_ex(
+            self.position.x as f32 - BUTTON_TOP_SIZE.x / 2.0,
+            self.position.y as f32 - BUTTON_TOP_SIZE.y / 2.0 - 1.0,
+            colors::WHITE,
+            DrawTextureParams {
+                source: Some(crate::new_texture_rect(
+                    BUTTON_TOP_POSITION,
+                    BUTTON_TOP_SIZE,
+                )),
+                ..Default::default()
+            },
+        );
+    }
+
     fn collision_rect(&self) -> Option<TileRect> {
         Some(TileRect::from_rect_inclusive(self.collision_rect()))
     }
@@ -119,6 +150,16 @@ impl Entity for Button {
         Box::new(self.clone())
     }
 
+    fn save_runtime_state(&self) -> Vec<u8> {
+        encode_runtime_state(&self.pressed)
+    }
+
+    fn load_runtime_state(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        self.pressed = decode_runtime_state(data)?;
+
+        Ok(())
+    }
+
     fn should_recieve_inputs(&self) -> bool {
         false
     }
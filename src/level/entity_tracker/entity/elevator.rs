@@ -3,6 +3,7 @@ use std::{
     mem,
 };
 
+use bincode::error::DecodeError;
 use macroquad::{
     color::{Color, colors},
     math::Rect,
@@ -20,15 +21,17 @@ use crate::{
         entity_tracker::{
             EntityTracker,
             entity::{
-                Entity, GameAction,
+                Entity, GameAction, decode_runtime_state,
                 elevator_door::{ElevatorDoor, ElevatorDoorOrientation},
                 empty::Empty,
+                encode_runtime_state,
                 logic_gate::{self, LogicGate},
                 player::PlayerState,
             },
         },
         light_grid::LightGrid,
     },
+    overview::Canvas,
 };
 
 pub const ELEVATOR_SIZE_INNER: Vector2<f64> = vector![16.0, 16.0];
@@ -74,7 +77,7 @@ pub struct Spark {
     pub max_age: u16,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum ElevatorState {
     Running {
         held_open: bool,
@@ -93,7 +96,7 @@ pub enum ElevatorState {
     Broken,
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
 pub enum ElevatorRunningState {
     #[default]
     Active,
@@ -212,13 +215,15 @@ impl Elevator {
     ) -> impl Iterator<Item = EntityKey> {
         let collision_rect = TileRect::from_rect_inclusive(rectangle);
 
+        // Crates stay behind when the elevator leaves, so they don't count as riding it.
         entities
             .into_iter()
             .filter(move |&(_, entity)| {
-                entity
-                    .inner
-                    .collision_rect()
-                    .is_some_and(|rect| function(&collision_rect, &rect))
+                entity.inner.as_crate().is_none()
+                    && entity
+                        .inner
+                        .collision_rect()
+                        .is_some_and(|rect| function(&collision_rect, &rect))
             })
             .map(|(key, _)| key)
     }
@@ -256,14 +261,18 @@ impl Elevator {
     }
 
     pub fn draw_symbol(&self, texture_atlas: &Texture2D, color: Color) {
+        let (position, params) = self.symbol_texture();
+
+        texture::draw_texture_ex(texture_atlas, position.x, position.y, color, params);
+    }
+
+    /// Where the symbol above the elevator goes, and how it's drawn from the texture atlas.
+    pub fn symbol_texture(&self) -> (Point2<f32>, DrawTextureParams) {
         let position = self.position.map(|x| x as f32) + 17.0 * self.direction.offset::<f32>()
             - ELEVATOR_SYMBOL_TEXTURE_SIZE / 2.0;
 
-        texture::draw_texture_ex(
-            texture_atlas,
-            position.x,
-            position.y,
-            color,
+        (
+            position,
             DrawTextureParams {
                 source: Some(crate::new_texture_rect(
                     ELEVATOR_SYMBOL_TEXTURE_POSITION
@@ -284,7 +293,7 @@ impl Elevator {
                 ),
                 ..Default::default()
             },
-        );
+        )
     }
 
     pub fn color_of_symbol(&self) -> Color {
@@ -501,7 +510,9 @@ impl Entity for Elevator {
                         }
 
                         for &mut expected_occupant in expected_occupants {
-                            entities[expected_occupant].inner = Box::new(Empty);
+                            let occupant = &mut entities[expected_occupant].inner;
+                            occupant.despawn(light_grid);
+                            *occupant = Box::new(Empty);
                         }
 
                         self.state = ElevatorState::Used;
@@ -691,6 +702,39 @@ impl Entity for Elevator {
         }
     }
 
+    fn draw_overview(&self, canvas: &mut Canvas) {
+        canvas.draw_texture_ex(
+            self.position.x as f32 - ELEVATOR_FLOOR_TEXTURE_SIZE.x / 2.0,
+            self.position.y as f32 - ELEVATOR_FLOOR_TEXTURE_SIZE.y / 2.0,
+            colors::WHITE,
+            DrawTextureParams {
+                source: Some(crate::new_texture_rect(
+                    ELEVATOR_FLOOR_TEXTURE_POSITION,
+                    ELEVATOR_FLOOR_TEXTURE_SIZE,
+                )),
+                ..Default::default()
+            },
+        );
+
+        canvas.draw_texture_ex(
+            self.position.x as f32 - ELEVATOR_WALLS_TEXTURE_SIZE.x / 2.0,
+            self.position.y as f32 - ELEVATOR_WALLS_TEXTURE_SIZE.y / 2.0,
+            colors::WHITE,
+            DrawTextureParams {
+                source: Some(crate::new_texture_rect(
+                    ELEVATOR_WALLS_TEXTURE_POSITION,
+                    ELEVATOR_WALLS_TEXTURE_SIZE,
+                )),
+                rotation: self.direction.angle() as f32,
+                ..Default::default()
+            },
+        );
+
+        let (position, params) = self.symbol_texture();
+
+        canvas.draw_texture_ex(position.x, position.y, colors::WHITE, params);
+    }
+
     fn position(&self) -> Point2<f64> {
         self.position
     }
@@ -723,6 +767,28 @@ impl Entity for Elevator {
         }
     }
 
+    fn save_runtime_state(&self) -> Vec<u8> {
+        encode_runtime_state(&(
+            self.powered,
+            self.animation_state,
+            self.damaged_brightness,
+            self.door,
+            &self.state,
+        ))
+    }
+
+    fn load_runtime_state(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        (
+            self.powered,
+            self.animation_state,
+            self.damaged_brightness,
+            self.door,
+            self.state,
+        ) = decode_runtime_state(data)?;
+
+        Ok(())
+    }
+
     fn should_recieve_inputs(&self) -> bool {
         false
     }
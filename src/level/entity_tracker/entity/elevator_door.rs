@@ -18,6 +18,7 @@ use crate::{
         },
         light_grid::{LightArea, LightGrid, Pixel},
     },
+    overview::Canvas,
 };
 
 pub const ELEVATOR_DOOR_TEXTURE_POSITION: Point2<f32> = point![0.0, 24.0];
@@ -88,6 +89,47 @@ impl ElevatorDoor {
         array::from_fn(|i| [corners[i], corners[(i + 1) % corners.len()]])
     }
 
+    /// Where each half of the door goes, and how it's drawn from the texture atlas.
+    pub fn half_textures(&self) -> [(Point2<f32>, DrawTextureParams); 2] {
+        let position = self.position.map(|x| x as f32) + ELEVATOR_DOOR_TEXTURE_OFFSET;
+
+        let hidden = (16 - self.extent) as f32;
+
+        let rotation = match self.orientation {
+            ElevatorDoorOrientation::Vertical => 0.0,
+            ElevatorDoorOrientation::Horizontal => PI / 2.0,
+        };
+
+        [
+            (
+                position,
+                DrawTextureParams {
+                    source: Some(crate::new_texture_rect(
+                        ELEVATOR_DOOR_TEXTURE_POSITION + vector![0.0, hidden],
+                        ELEVATOR_DOOR_TEXTURE_SIZE - vector![0.0, hidden],
+                    )),
+                    rotation,
+                    pivot: Some(self.position.map(|x| x as f32).into()),
+                    ..Default::default()
+                },
+            ),
+            (
+                position + vector![0.0, hidden],
+                DrawTextureParams {
+                    source: Some(crate::new_texture_rect(
+                        ELEVATOR_DOOR_TEXTURE_POSITION + vector![0.0, hidden],
+                        ELEVATOR_DOOR_TEXTURE_SIZE - vector![0.0, hidden],
+                    )),
+                    flip_x: true,
+                    flip_y: true,
+                    rotation,
+                    pivot: Some(self.position.map(|x| x as f32).into()),
+                    ..Default::default()
+                },
+            ),
+        ]
+    }
+
     pub fn collision_rect(&self) -> TileRect {
         TileRect {
             origin: (self.position + self.offset()).map(|x| x.floor() as isize),
@@ -160,58 +202,22 @@ impl Entity for ElevatorDoor {
     }
 
     fn draw_wall(&mut self, texture_atlas: &Texture2D) {
-        let position = self.position.map(|x| x as f32) + ELEVATOR_DOOR_TEXTURE_OFFSET;
-
-        let hidden = (16 - self.extent) as f32;
-
-        let rotation = match self.orientation {
-            ElevatorDoorOrientation::Vertical => 0.0,
-            ElevatorDoorOrientation::Horizontal => PI / 2.0,
-        };
+        for (position, params) in self.half_textures() {
+            texture::draw_texture_ex(texture_atlas, position.x, position.y, colors::WHITE, params);
+        }
+    }
 
-        texture::draw_texture_ex(
-            texture_atlas,
-            position.x,
-            position.y,
-            colors::WHITE,
-            DrawTextureParams {
-                source: Some(crate::new_texture_rect(
-                    ELEVATOR_DOOR_TEXTURE_POSITION + vector![0.0, hidden],
-                    ELEVATOR_DOOR_TEXTURE_SIZE - vector![0.0, hidden],
-                )),
-                rotation,
-                pivot: Some(self.position.map(|x| x as f32).into()),
-                ..Default::default()
-            },
-        );
-
-        texture::draw_texture_ex(
-            texture_atlas,
-            position.x,
-            position.y + hidden,
-            colors::WHITE,
-            DrawTextureParams {
-                source: Some(crate::new_texture_rect(
-                    ELEVATOR_DOOR_TEXTURE_POSITION + vector![0.0, hidden],
-                    ELEVATOR_DOOR_TEXTURE_SIZE - vector![0.0, hidden],
-                )),
-                flip_x: true,
-                flip_y: true,
-                rotation,
-                pivot: Some(self.position.map(|x| x as f32).into()),
-                ..Default::default()
-            },
-        );
+    fn draw_overview(&self, canvas: &mut Canvas) {
+        for (position, params) in self.half_textures() {
+            canvas.draw_texture_ex(position.x, position.y, colors::WHITE, params);
+        }
     }
 
     fn is_within_view_area(&self, light_grid: &LightGrid, view_area: &LightArea) -> bool {
         self.edges()
             .into_iter()
             .any(|line| view_area.edge_intersects_line(line))
-            || view_area
-                .range
-                .is_none_or(|range| range.contains_offset(self.position - view_area.origin))
-                && light_grid.contains_path(view_area.origin, self.position)
+            || view_area.sees_point(light_grid, self.position)
     }
 
     fn visible_state(&self) -> Option<EntityVisibleState> {
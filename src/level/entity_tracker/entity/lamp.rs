@@ -0,0 +1,209 @@
+use bincode::error::DecodeError;
+use macroquad::{color::Color, shapes, texture::Texture2D};
+use nalgebra::{Point2, Vector2, vector};
+use serde::{Deserialize, Serialize};
+use slotmap::SlotMap;
+
+use crate::{
+    collections::{history::FrameIndex, slot_guard::GuardedSlotMap},
+    level::{
+        EntityKey,
+        entity_tracker::{
+            EntityTracker,
+            entity::{
+                Entity, EntityVisibleState, GameAction, decode_runtime_state, encode_runtime_state,
+            },
+        },
+        light_grid::{LightArea, LightGrid},
+    },
+    overview::Canvas,
+};
+
+pub const LAMP_SIZE: Vector2<f64> = vector![4.0, 4.0];
+pub const DEFAULT_LAMP_RADIUS: f64 = 48.0;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Lamp {
+    pub position: Point2<f64>,
+    /// How far the light reaches, in pixels.
+    pub radius: f64,
+    pub input: Option<EntityKey>,
+
+    #[serde(skip)]
+    pub powered: Option<bool>,
+    #[serde(skip)]
+    pub light_area: Option<LightArea>,
+}
+
+impl Lamp {
+    pub fn new(position: Point2<f64>, radius: f64) -> Self {
+        Self {
+            position,
+            radius,
+            input: None,
+
+            powered: None,
+            light_area: None,
+        }
+    }
+
+    /// Lamps without an input are always on.
+    pub fn is_on(&self) -> bool {
+        self.powered.unwrap_or(true)
+    }
+}
+
+/// The areas lit by every light source in `entities`, or `None` if there aren't any, in which case
+/// the level is lit everywhere.
+pub fn lit_areas<'a>(
+    entities: impl IntoIterator<Item = &'a EntityTracker>,
+) -> Option<Vec<&'a LightArea>> {
+    let mut dark = false;
+    let mut areas = Vec::new();
+
+    for entity in entities {
+        dark |= entity.inner.is_light_source();
+        areas.extend(entity.inner.light_area());
+    }
+
+    dark.then_some(areas)
+}
+
+#[typetag::serde]
+impl Entity for Lamp {
+    fn update(
+        &mut self,
+        _frame: FrameIndex,
+        _entities: GuardedSlotMap<EntityKey, EntityTracker>,
+        _light_grid: &mut LightGrid,
+        _initial_state: &mut SlotMap<EntityKey, EntityTracker>,
+    ) -> Option<GameAction> {
+        None
+    }
+
+    fn update_view_area(&mut self, light_grid: &mut LightGrid) {
+        self.light_area = self.is_on().then(|| {
+            light_grid
+                .trace_light_from(self.position, None)
+                .with_radius(Some(self.radius))
+        });
+    }
+
+    fn draw_back(&mut self, _texture_atlas: &Texture2D) {
+        let corner = self.position - LAMP_SIZE / 2.0;
+
+        shapes::draw_rectangle(
+            corner.x as f32,
+            corner.y as f32,
+            LAMP_SIZE.x as f32,
+            LAMP_SIZE.y as f32,
+            Color::from_hex(0x4a4658),
+        );
+
+        shapes::draw_rectangle(
+            corner.x as f32 + 1.0,
+            corner.y as f32 + 1.0,
+            LAMP_SIZE.x as f32 - 2.0,
+            LAMP_SIZE.y as f32 - 2.0,
+            if self.is_on() {
+                Color::from_hex(0xffe08a)
+            } else {
+                Color::from_hex(0x6e6450)
+            },
+        );
+    }
+
+    fn draw_overview(&self, canvas: &mut Canvas) {
+        let corner = self.position - LAMP_SIZE / 2.0;
+
+        canvas.draw_rectangle(
+            corner.x as f32,
+            corner.y as f32,
+            LAMP_SIZE.x as f32,
+            LAMP_SIZE.y as f32,
+            Color::from_hex(0x4a4658),
+        );
+
+        canvas.draw_rectangle(
+            corner.x as f32 + 1.0,
+            corner.y as f32 + 1.0,
+            LAMP_SIZE.x as f32 - 2.0,
+            LAMP_SIZE.y as f32 - 2.0,
+            Color::from_hex(0xffe08a),
+        );
+    }
+
+    fn light_area(&self) -> Option<&LightArea> {
+        self.light_area.as_ref()
+    }
+
+    fn is_light_source(&self) -> bool {
+        true
+    }
+
+    fn is_within_view_area(&self, light_grid: &LightGrid, view_area: &LightArea) -> bool {
+        view_area.sees_point(light_grid, self.position)
+    }
+
+    fn visible_state(&self) -> Option<EntityVisibleState> {
+        Some(EntityVisibleState::new(self.position, self.is_on() as u64))
+    }
+
+    fn position(&self) -> Point2<f64> {
+        self.position
+    }
+
+    fn position_mut(&mut self) -> Option<&mut Point2<f64>> {
+        Some(&mut self.position)
+    }
+
+    fn duplicate(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
+
+    fn save_runtime_state(&self) -> Vec<u8> {
+        encode_runtime_state(&self.powered)
+    }
+
+    fn load_runtime_state(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        self.powered = decode_runtime_state(data)?;
+
+        Ok(())
+    }
+
+    fn should_recieve_inputs(&self) -> bool {
+        false
+    }
+
+    fn inputs(&self) -> &[EntityKey] {
+        self.input.as_slice()
+    }
+
+    fn try_add_input(&mut self, key: EntityKey) {
+        if self.input.is_none() {
+            self.input = Some(key);
+        }
+    }
+
+    fn try_remove_input(&mut self, key: EntityKey) {
+        if self.input == Some(key) {
+            self.input = None;
+        }
+    }
+
+    fn evaluate(
+        &mut self,
+        _entities: GuardedSlotMap<EntityKey, EntityTracker>,
+        inputs: &[bool],
+    ) -> bool {
+        self.powered = inputs.first().copied();
+
+        self.is_on()
+    }
+
+    fn offset_of_wire(&self, wire_end: Vector2<f64>) -> Vector2<f64> {
+        const DISTANCE: f64 = 2.0;
+
+        wire_end.map(|x| x.clamp(-DISTANCE, DISTANCE))
+    }
+}
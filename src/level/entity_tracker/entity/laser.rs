@@ -0,0 +1,283 @@
+use bincode::error::DecodeError;
+use macroquad::{
+    color::{Color, colors},
+    math::Rect,
+    shapes,
+    texture::Texture2D,
+};
+use nalgebra::{Point2, UnitVector2, Vector2, vector};
+use serde::{Deserialize, Serialize};
+use slotmap::SlotMap;
+
+use crate::{
+    collections::{history::FrameIndex, slot_guard::GuardedSlotMap},
+    level::{
+        EntityKey,
+        entity_tracker::{
+            EntityTracker,
+            entity::{
+                Entity, EntityVisibleState, GameAction, decode_runtime_state,
+                elevator::ElevatorDirection, encode_runtime_state,
+            },
+        },
+        light_grid::{self, LightArea, LightGrid},
+    },
+    overview::Canvas,
+};
+
+pub const LASER_EMITTER_SIZE: Vector2<f64> = vector![4.0, 4.0];
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Laser {
+    pub position: Point2<f64>,
+    pub direction: ElevatorDirection,
+    pub kind: LaserKind,
+    pub input: Option<EntityKey>,
+
+    #[serde(skip)]
+    pub powered: Option<bool>,
+    #[serde(skip)]
+    pub beam_end: Option<Point2<f64>>,
+    #[serde(skip)]
+    pub tripped: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum LaserKind {
+    /// Kills any player that crosses the beam.
+    Lethal,
+    /// Harmless, only useful for its output.
+    Tripwire,
+}
+
+impl Laser {
+    pub fn new(position: Point2<f64>, direction: ElevatorDirection, kind: LaserKind) -> Self {
+        Self {
+            position,
+            direction,
+            kind,
+            input: None,
+
+            powered: None,
+            beam_end: None,
+            tripped: false,
+        }
+    }
+
+    /// Lasers without an input are always on.
+    pub fn is_on(&self) -> bool {
+        self.powered.unwrap_or(true)
+    }
+
+    pub fn beam_start(&self) -> Point2<f64> {
+        self.position
+            + self
+                .direction
+                .offset::<f64>()
+                .component_mul(&(LASER_EMITTER_SIZE / 2.0))
+    }
+
+    /// Check if the beam passes through the inside of `rect`. Touching its edge doesn't count.
+    pub fn beam_intersects(&self, rect: Rect) -> bool {
+        let Some(end) = self.beam_end else {
+            return false;
+        };
+        let start = self.beam_start();
+
+        let min = start.inf(&end).map(|x| x as f32);
+        let max = start.sup(&end).map(|x| x as f32);
+
+        // The beam is always axis aligned, so along one axis it's just a line.
+        let overlaps = |rect_min: f32, rect_max: f32, min: f32, max: f32| {
+            if min == max {
+                rect_min < min && min < rect_max
+            } else {
+                rect_min <= max && min <= rect_max
+            }
+        };
+
+        overlaps(rect.left(), rect.right(), min.x, max.x)
+            && overlaps(rect.top(), rect.bottom(), min.y, max.y)
+    }
+}
+
+#[typetag::serde]
+impl Entity for Laser {
+    fn update(
+        &mut self,
+        _frame: FrameIndex,
+        _entities: GuardedSlotMap<EntityKey, EntityTracker>,
+        light_grid: &mut LightGrid,
+        _initial_state: &mut SlotMap<EntityKey, EntityTracker>,
+    ) -> Option<GameAction> {
+        self.beam_end = self.is_on().then(|| {
+            light_grid::raycast(
+                |_, index| light_grid[index].blocks_light(),
+                self.beam_start(),
+                UnitVector2::new_unchecked(self.direction.offset()),
+                LightGrid::MAXIMUM_RAY_RANGE,
+            )
+            .0
+        });
+
+        None
+    }
+
+    fn draw_back(&mut self, _texture_atlas: &Texture2D) {
+        if let Some(end) = self.beam_end {
+            let start = self.beam_start();
+
+            shapes::draw_line(
+                start.x as f32,
+                start.y as f32,
+                end.x as f32,
+                end.y as f32,
+                1.0,
+                match self.kind {
+                    LaserKind::Lethal => Color::new(1.0, 0.1, 0.1, 0.8),
+                    LaserKind::Tripwire if self.tripped => Color::new(1.0, 0.8, 0.2, 0.6),
+                    LaserKind::Tripwire => Color::new(1.0, 0.4, 0.4, 0.4),
+                },
+            );
+        }
+
+        let corner = self.position - LASER_EMITTER_SIZE / 2.0;
+
+        shapes::draw_rectangle(
+            corner.x as f32,
+            corner.y as f32,
+            LASER_EMITTER_SIZE.x as f32,
+            LASER_EMITTER_SIZE.y as f32,
+            Color::from_hex(0x4a4658),
+        );
+
+        let lens = self.beam_start() - self.direction.offset::<f64>();
+
+        shapes::draw_rectangle(
+            lens.x as f32 - 0.5,
+            lens.y as f32 - 0.5,
+            1.0,
+            1.0,
+            if self.is_on() {
+                colors::RED
+            } else {
+                colors::MAROON
+            },
+        );
+    }
+
+    fn draw_overview(&self, canvas: &mut Canvas) {
+        if let Some(end) = self.beam_end {
+            let start = self.beam_start();
+
+            canvas.draw_line(
+                start.x as f32,
+                start.y as f32,
+                end.x as f32,
+                end.y as f32,
+                1.0,
+                match self.kind {
+                    LaserKind::Lethal => Color::new(1.0, 0.1, 0.1, 0.8),
+                    LaserKind::Tripwire => Color::new(1.0, 0.4, 0.4, 0.4),
+                },
+            );
+        }
+
+        let corner = self.position - LASER_EMITTER_SIZE / 2.0;
+
+        canvas.draw_rectangle(
+            corner.x as f32,
+            corner.y as f32,
+            LASER_EMITTER_SIZE.x as f32,
+            LASER_EMITTER_SIZE.y as f32,
+            Color::from_hex(0x4a4658),
+        );
+
+        let lens = self.beam_start() - self.direction.offset::<f64>();
+
+        canvas.draw_rectangle(
+            lens.x as f32 - 0.5,
+            lens.y as f32 - 0.5,
+            1.0,
+            1.0,
+            colors::RED,
+        );
+    }
+
+    fn is_within_view_area(&self, light_grid: &LightGrid, view_area: &LightArea) -> bool {
+        view_area.sees_point(light_grid, self.position)
+    }
+
+    fn visible_state(&self) -> Option<EntityVisibleState> {
+        Some(EntityVisibleState::new(self.position, self.is_on() as u64))
+    }
+
+    fn is_lethal_to(&self, rect: Rect) -> bool {
+        self.kind == LaserKind::Lethal && self.beam_intersects(rect)
+    }
+
+    fn position(&self) -> Point2<f64> {
+        self.position
+    }
+
+    fn position_mut(&mut self) -> Option<&mut Point2<f64>> {
+        Some(&mut self.position)
+    }
+
+    fn duplicate(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
+
+    fn save_runtime_state(&self) -> Vec<u8> {
+        encode_runtime_state(&(self.powered, self.beam_end, self.tripped))
+    }
+
+    fn load_runtime_state(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        (self.powered, self.beam_end, self.tripped) = decode_runtime_state(data)?;
+
+        Ok(())
+    }
+
+    fn should_recieve_inputs(&self) -> bool {
+        false
+    }
+
+    fn inputs(&self) -> &[EntityKey] {
+        self.input.as_slice()
+    }
+
+    fn try_add_input(&mut self, key: EntityKey) {
+        if self.input.is_none() {
+            self.input = Some(key);
+        }
+    }
+
+    fn try_remove_input(&mut self, key: EntityKey) {
+        if self.input == Some(key) {
+            self.input = None;
+        }
+    }
+
+    fn evaluate(
+        &mut self,
+        entities: GuardedSlotMap<EntityKey, EntityTracker>,
+        inputs: &[bool],
+    ) -> bool {
+        self.powered = inputs.first().copied();
+
+        self.tripped = entities.iter().any(|(_, entity)| {
+            entity
+                .inner
+                .as_player()
+                .is_some_and(|player| self.beam_intersects(player.collision_rect()))
+        });
+
+        self.tripped
+    }
+
+    fn offset_of_wire(&self, wire_end: Vector2<f64>) -> Vector2<f64> {
+        const DISTANCE: f64 = 2.0;
+
+        wire_end.map(|x| x.clamp(-DISTANCE, DISTANCE))
+    }
+}
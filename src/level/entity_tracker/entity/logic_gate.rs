@@ -1,5 +1,6 @@
 use std::f64::consts::PI;
 
+use bincode::error::DecodeError;
 use macroquad::{
     color::Color,
     texture::{self, DrawTextureParams, Texture2D},
@@ -14,10 +15,11 @@ use crate::{
         EntityKey, UPDATE_TPS,
         entity_tracker::{
             EntityTracker,
-            entity::{Entity, GameAction},
+            entity::{Entity, GameAction, decode_runtime_state, encode_runtime_state},
         },
         light_grid::LightGrid,
     },
+    overview::Canvas,
 };
 
 pub const LOGIC_GATE_TEXTURE_START: Point2<f32> = point![32.0, 48.0];
@@ -88,27 +90,9 @@ impl LogicGateKind {
 
 impl LogicGate {
     pub const ANIMATION_STEP: u16 = (u16::MAX as usize * 10 / UPDATE_TPS) as u16;
-}
-
-#[typetag::serde]
-impl Entity for LogicGate {
-    fn update(
-        &mut self,
-        _frame: FrameIndex,
-        _entities: GuardedSlotMap<EntityKey, EntityTracker>,
-        _light_grid: &mut LightGrid,
-        _initial_state: &mut SlotMap<EntityKey, EntityTracker>,
-    ) -> Option<GameAction> {
-        self.animation_state = if self.powered.unwrap_or(false) {
-            self.animation_state.saturating_add(Self::ANIMATION_STEP)
-        } else {
-            self.animation_state.saturating_sub(Self::ANIMATION_STEP)
-        };
 
-        None
-    }
-
-    fn draw_overlay_front(&mut self, texture_atlas: &Texture2D) {
+    /// Where the gate's symbol goes, and how it's drawn from the texture atlas, if it has one.
+    pub fn symbol_texture(&self) -> Option<(Point2<f32>, DrawTextureParams)> {
         let texture_position = LOGIC_GATE_TEXTURE_START
             + LOGIC_GATE_TEXTURE_SIZE.component_mul(&match self.kind {
                 LogicGateKind::And => vector![0.0, 0.0],
@@ -118,16 +102,15 @@ impl Entity for LogicGate {
                 LogicGateKind::Toggle { .. } => vector![4.0, 0.0],
                 LogicGateKind::Hold { .. } => vector![5.0, 0.0],
                 LogicGateKind::ElevatorOutput => vector![6.0, 0.0],
-                LogicGateKind::Start | LogicGateKind::End | LogicGateKind::Delay { .. } => return,
+                LogicGateKind::Start | LogicGateKind::End | LogicGateKind::Delay { .. } => {
+                    return None;
+                }
             });
 
         let position = self.position.map(|x| x as f32) - LOGIC_GATE_TEXTURE_SIZE / 2.0;
 
-        texture::draw_texture_ex(
-            texture_atlas,
-            position.x,
-            position.y,
-            self.power_color().unwrap(),
+        Some((
+            position,
             DrawTextureParams {
                 source: Some(crate::new_texture_rect(
                     texture_position,
@@ -136,7 +119,44 @@ impl Entity for LogicGate {
                 rotation: self.direction.angle() as f32,
                 ..Default::default()
             },
-        );
+        ))
+    }
+}
+
+#[typetag::serde]
+impl Entity for LogicGate {
+    fn update(
+        &mut self,
+        _frame: FrameIndex,
+        _entities: GuardedSlotMap<EntityKey, EntityTracker>,
+        _light_grid: &mut LightGrid,
+        _initial_state: &mut SlotMap<EntityKey, EntityTracker>,
+    ) -> Option<GameAction> {
+        self.animation_state = if self.powered.unwrap_or(false) {
+            self.animation_state.saturating_add(Self::ANIMATION_STEP)
+        } else {
+            self.animation_state.saturating_sub(Self::ANIMATION_STEP)
+        };
+
+        None
+    }
+
+    fn draw_overlay_front(&mut self, texture_atlas: &Texture2D) {
+        if let Some((position, params)) = self.symbol_texture() {
+            texture::draw_texture_ex(
+                texture_atlas,
+                position.x,
+                position.y,
+                self.power_color().unwrap(),
+                params,
+            );
+        }
+    }
+
+    fn draw_overview(&self, canvas: &mut Canvas) {
+        if let Some((position, params)) = self.symbol_texture() {
+            canvas.draw_texture_ex(position.x, position.y, self.power_color().unwrap(), params);
+        }
     }
 
     fn position(&self) -> Point2<f64> {
@@ -151,6 +171,16 @@ impl Entity for LogicGate {
         Box::new(self.clone())
     }
 
+    fn save_runtime_state(&self) -> Vec<u8> {
+        encode_runtime_state(&(self.powered, self.animation_state))
+    }
+
+    fn load_runtime_state(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        (self.powered, self.animation_state) = decode_runtime_state(data)?;
+
+        Ok(())
+    }
+
     fn should_recieve_inputs(&self) -> bool {
         false
     }
@@ -1,13 +1,14 @@
 use std::{array, cmp::Ordering, f64::consts::PI, mem};
 
+use bincode::error::DecodeError;
 use macroquad::{
     color::{Color, colors},
-    input::KeyCode,
+    input::{KeyCode, MouseButton},
     math::Rect,
     shapes,
     texture::{self, DrawTextureParams, Texture2D},
 };
-use nalgebra::{Point2, UnitVector2, Vector2, point, vector};
+use nalgebra::{Point2, UnitComplex, UnitVector2, Vector2, point, vector};
 use serde::{Deserialize, Serialize};
 use slotmap::{SecondaryMap, SlotMap};
 
@@ -17,15 +18,20 @@ use crate::{
         slot_guard::GuardedSlotMap,
         tile_grid::TileRect,
     },
-    input::DirectionalInput,
+    input::{self, Action, AimMode, AnalogInput, AxialInput, DirectionalInput},
     level::{
         EntityKey, UPDATE_DT,
         entity_tracker::{
             EntityTracker,
-            entity::{Entity, EntityVisibleState, GameAction, ViewKind},
+            entity::{
+                Entity, EntityVisibleState, GameAction, ViewKind, decode_runtime_state,
+                encode_runtime_state, lamp, pushable_crate::CRATE_SIZE,
+            },
         },
-        light_grid::{AngleRange, LightArea, LightGrid},
+        light_grid::{AngleRange, LightArea, LightGrid, Pixel},
+        paradox_inspector,
     },
+    overview::Canvas,
 };
 
 pub const CONFUSION_EFFECT_START: Point2<f32> = point![0.0, 16.0];
@@ -54,11 +60,20 @@ pub struct Player {
     pub mouse_position: Point2<f64>,
     pub view_direction: UnitVector2<f64>,
     pub view_width: f64,
+    /// How far the player can see, if not only limited by walls. Stored in the level's
+    /// `LevelMetadata::view_radii` rather than with the rest of the player.
+    #[serde(skip)]
+    pub view_radius: Option<f64>,
 
     #[serde(skip)]
-    pub wasd_motion_input: DirectionalInput,
-    #[serde(skip, default = "arrow_inputs")]
-    pub arrows_motion_input: DirectionalInput,
+    pub motion_input: DirectionalInput,
+    #[serde(skip, default = "rotate_input")]
+    pub rotate_input: AxialInput,
+    #[serde(skip)]
+    pub analog_input: AnalogInput,
+    /// How the view cone was aimed on the last update.
+    #[serde(skip)]
+    pub aim_mode: AimMode,
     pub speed: f64,
 
     pub state: PlayerState,
@@ -69,13 +84,19 @@ pub struct Player {
 
     pub confusion: f64,
     pub paradox_position: Option<(f64, Point2<f64>)>,
+    #[serde(skip)]
+    pub paradox_report: Option<ParadoxReport>,
 
     #[serde(skip)]
     pub view_area: Option<LightArea>,
 }
 
-pub fn arrow_inputs() -> DirectionalInput {
-    DirectionalInput::new(KeyCode::Right, KeyCode::Up, KeyCode::Left, KeyCode::Down)
+pub fn rotate_input() -> AxialInput {
+    AxialInput::from_key_bindings(
+        &input::key_bindings(),
+        Action::RotateClockwise,
+        Action::RotateCounterclockwise,
+    )
 }
 
 impl Default for Player {
@@ -87,10 +108,13 @@ impl Default for Player {
             mouse_position: point![0.0, 0.0],
             view_direction: UnitVector2::new_normalize(vector![1.0, 0.0]),
             view_width: 120.0 * PI / 180.0,
+            view_radius: None,
 
             speed: 64.0,
-            wasd_motion_input: DirectionalInput::default(),
-            arrows_motion_input: arrow_inputs(),
+            motion_input: DirectionalInput::default(),
+            rotate_input: rotate_input(),
+            analog_input: AnalogInput::default(),
+            aim_mode: AimMode::default(),
 
             state: PlayerState::Active,
             history: History::default(),
@@ -98,6 +122,7 @@ impl Default for Player {
 
             confusion: 0.0,
             paradox_position: None,
+            paradox_report: None,
 
             view_area: None,
         }
@@ -112,19 +137,71 @@ pub enum PlayerState {
     Future,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
 pub struct PlayerHistoryEntry {
     pub position: Point2<f32>,
     pub mouse_position: Point2<f32>,
+    /// Whatever the mode, `mouse_position` is where the view cone pointed.
+    pub aim_mode: AimMode,
+}
+
+/// One entity a past self compared against what it saw when it was recorded.
+#[derive(Clone, Copy, Debug)]
+pub struct ParadoxComparison {
+    pub key: EntityKey,
+    pub observed: Option<EntityVisibleState>,
+    pub recorded: Option<EntityVisibleState>,
+    pub error: Option<f64>,
+}
+
+impl ParadoxComparison {
+    pub fn position(&self) -> Point2<f64> {
+        self.observed.or(self.recorded).unwrap().position()
+    }
+}
+
+/// Every comparison a past self made against a single recorded frame.
+#[derive(Clone, Default, Debug)]
+pub struct ParadoxReport {
+    pub frame: FrameIndex,
+    pub comparisons: Vec<ParadoxComparison>,
+}
+
+impl ParadoxReport {
+    /// The comparison with the highest error. This is the one that decides the paradox level.
+    pub fn worst(&self) -> Option<&ParadoxComparison> {
+        let mut worst: Option<&ParadoxComparison> = None;
+
+        for comparison in &self.comparisons {
+            if worst.is_none_or(|worst| comparison.error > worst.error) {
+                worst = Some(comparison);
+            }
+        }
+
+        worst.filter(|worst| worst.error.is_some())
+    }
+
+    pub fn paradox_level(&self) -> Option<(f64, Point2<f64>)> {
+        let worst = self.worst()?;
+
+        Some((worst.error?, worst.position()))
+    }
 }
 
 impl Player {
     pub const CONFUSION_TIME: f64 = 0.1;
     pub const CONFUSION_FALLOFF_DISTANCE: f64 = 24.0;
     pub const CONFUSION_DISTANCE_THRESHOLD: f64 = 12.0;
+    /// How close something has to be to be seen without being lit, in levels with lamps.
+    pub const DARK_SIGHT_DISTANCE: f64 = 12.0;
 
     pub const RECOVERY_TIME: f64 = 5.0;
 
+    /// How far from the player the mouse position is put when not aiming with the mouse.
+    pub const AIM_DISTANCE: f64 = 32.0;
+    /// In radians per second, for `AimMode::RotateKeys`.
+    pub const ROTATION_SPEED: f64 = PI;
+
     pub fn collision_rect(&self) -> Rect {
         let corner = self.position - self.size / 2.0;
 
@@ -140,7 +217,40 @@ impl Player {
         PlayerHistoryEntry {
             position: self.position.map(|x| x as f32),
             mouse_position: self.mouse_position.map(|x| x as f32),
+            aim_mode: self.aim_mode,
+        }
+    }
+
+    /// Aims the view cone according to the current `AimMode`, given the direction of movement.
+    /// Stick and keyboard aiming go through the mouse position so that they're recorded the same
+    /// way as the mouse.
+    fn aim(&mut self, movement: Vector2<f64>) {
+        self.aim_mode = input::aim_mode();
+
+        if let Some(aim) = self.analog_input.aim {
+            self.mouse_position = self.position + aim * Self::AIM_DISTANCE;
+        } else {
+            match self.aim_mode {
+                AimMode::Mouse => (),
+                AimMode::RotateKeys => {
+                    let rotation =
+                        self.rotate_input.output as f64 * Self::ROTATION_SPEED * UPDATE_DT;
+                    let direction = UnitComplex::new(rotation) * self.view_direction;
+
+                    self.mouse_position =
+                        self.position + direction.into_inner() * Self::AIM_DISTANCE;
+                }
+                AimMode::Movement => {
+                    let direction =
+                        UnitVector2::try_new(movement, f64::EPSILON).unwrap_or(self.view_direction);
+
+                    self.mouse_position =
+                        self.position + direction.into_inner() * Self::AIM_DISTANCE;
+                }
+            }
         }
+
+        self.update_view_direction();
     }
 
     pub fn update_view_direction(&mut self) {
@@ -178,17 +288,92 @@ impl Player {
         array::from_fn(|i| [corners[i], corners[(i + 1) % corners.len()]])
     }
 
+    pub fn touches_hazard(
+        &self,
+        entities: &GuardedSlotMap<EntityKey, EntityTracker>,
+        light_grid: &LightGrid,
+    ) -> bool {
+        let collision_rect = self.collision_rect();
+        let bounds = TileRect::from_rect_inclusive(collision_rect);
+
+        (bounds.left()..bounds.right() + 1).any(|x| {
+            (bounds.top()..bounds.bottom() + 1).any(|y| light_grid[point![x, y]].is_lethal())
+        }) || entities
+            .iter()
+            .any(|(_, entity)| entity.inner.is_lethal_to(collision_rect))
+    }
+
+    /// Whether `entity` is in view, and also lit or close enough to make out in the dark.
+    /// `lit_areas` comes from `lamp::lit_areas`.
+    pub fn sees(
+        &self,
+        entity: &dyn Entity,
+        light_grid: &LightGrid,
+        view_area: &LightArea,
+        lit_areas: Option<&[&LightArea]>,
+    ) -> bool {
+        entity.is_within_view_area(light_grid, view_area)
+            && lit_areas.is_none_or(|lit_areas| {
+                (entity.position() - self.position).magnitude_squared()
+                    <= Self::DARK_SIGHT_DISTANCE.powi(2)
+                    || lit_areas
+                        .iter()
+                        .any(|area| entity.is_within_view_area(light_grid, area))
+            })
+    }
+
+    /// The worst comparison `compare_frame` makes, without keeping any of them.
     pub fn paradox_level(
         &self,
         frame: FrameIndex,
         entities: &GuardedSlotMap<EntityKey, EntityTracker>,
         light_grid: &LightGrid,
     ) -> Option<(f64, Point2<f64>)> {
-        let view_area = self.view_area.as_ref()?;
-        let mut exists = SecondaryMap::default();
+        let mut worst: Option<ParadoxComparison> = None;
+
+        self.compare_frame(frame, entities, light_grid, |comparison| {
+            if comparison.error > worst.and_then(|worst| worst.error) {
+                worst = Some(comparison);
+            }
+        });
+
+        let worst = worst?;
+
+        Some((worst.error?, worst.position()))
+    }
+
+    /// Every comparison `compare_frame` makes, for the paradox inspector.
+    pub fn paradox_report(
+        &self,
+        frame: FrameIndex,
+        entities: &GuardedSlotMap<EntityKey, EntityTracker>,
+        light_grid: &LightGrid,
+    ) -> Option<ParadoxReport> {
+        let mut report = ParadoxReport {
+            frame,
+            comparisons: Vec::new(),
+        };
 
-        let mut error = None;
-        let mut position = None;
+        self.compare_frame(frame, entities, light_grid, |comparison| {
+            report.comparisons.push(comparison);
+        })
+        .then_some(report)
+    }
+
+    /// Compare everything currently visible against what was seen on `frame` when this player was
+    /// recorded, passing each comparison to `compare`. Returns false if the player can't see.
+    fn compare_frame(
+        &self,
+        frame: FrameIndex,
+        entities: &GuardedSlotMap<EntityKey, EntityTracker>,
+        light_grid: &LightGrid,
+        mut compare: impl FnMut(ParadoxComparison),
+    ) -> bool {
+        let Some(view_area) = &self.view_area else {
+            return false;
+        };
+        let lit_areas = lamp::lit_areas(entities.iter().map(|(_, entity)| entity));
+        let mut exists = SecondaryMap::default();
 
         for (key, entity) in entities.iter() {
             exists.insert(key, ());
@@ -197,7 +382,7 @@ impl Player {
                 continue;
             };
 
-            if !entity.inner.is_within_view_area(light_grid, view_area) {
+            if !self.sees(&*entity.inner, light_grid, view_area, lit_areas.as_deref()) {
                 current_state = None;
             };
 
@@ -207,11 +392,16 @@ impl Player {
                 .and_then(|history| history.get(frame))
                 .copied();
 
-            let this_error = self.compare_states(current_state, expected_state);
-            if this_error > error {
-                error = this_error;
-                position = Some(current_state.or(expected_state).unwrap().position());
+            if current_state.is_none() && expected_state.is_none() {
+                continue;
             }
+
+            compare(ParadoxComparison {
+                key,
+                observed: current_state,
+                recorded: expected_state,
+                error: self.compare_states(current_state, expected_state),
+            });
         }
 
         for (key, history) in &self.environment_history {
@@ -220,15 +410,16 @@ impl Player {
             }
 
             if let Some(expected_state) = history.get(frame).copied() {
-                let this_error = self.compare_states(None, Some(expected_state));
-                if this_error > error {
-                    error = this_error;
-                    position = Some(expected_state.position());
-                }
+                compare(ParadoxComparison {
+                    key,
+                    observed: None,
+                    recorded: Some(expected_state),
+                    error: self.compare_states(None, Some(expected_state)),
+                });
             }
         }
 
-        error.zip(position)
+        true
     }
 
     pub fn compare_states(
@@ -271,7 +462,7 @@ impl Entity for Player {
     fn update(
         &mut self,
         frame: FrameIndex,
-        entities: GuardedSlotMap<EntityKey, EntityTracker>,
+        mut entities: GuardedSlotMap<EntityKey, EntityTracker>,
         light_grid: &mut LightGrid,
         _initial_state: &mut SlotMap<EntityKey, EntityTracker>,
     ) -> Option<GameAction> {
@@ -279,26 +470,33 @@ impl Entity for Player {
 
         match self.state {
             PlayerState::Active => {
-                let input = if self.arrows_motion_input.raw_output() == vector![0, 0] {
-                    &self.wasd_motion_input
+                let direction = if self.analog_input.movement == vector![0.0, 0.0] {
+                    self.motion_input.normalized_output()
                 } else {
-                    &self.arrows_motion_input
+                    self.analog_input.movement
                 };
 
-                let motion = input.normalized_output() * self.speed * UPDATE_DT;
+                let motion = direction * self.speed * UPDATE_DT;
 
-                self.move_along_axis::<0>(light_grid, motion.x);
-                self.move_along_axis::<1>(light_grid, motion.y);
+                self.move_along_axis::<0>(&mut entities, light_grid, motion.x);
+                self.move_along_axis::<1>(&mut entities, light_grid, motion.y);
 
-                self.update_view_direction();
+                self.aim(direction);
 
                 self.history.try_insert(frame, self.get_history_entry());
 
+                if self.touches_hazard(&entities, light_grid) {
+                    self.state = PlayerState::Dead;
+                    return Some(GameAction::PlayerDied);
+                }
+
                 self.paradox_position = None;
 
                 if let Some(view_area) = &self.view_area {
+                    let lit_areas = lamp::lit_areas(entities.iter().map(|(_, entity)| entity));
+
                     for (key, entity) in entities.iter() {
-                        if entity.inner.is_within_view_area(light_grid, view_area) {
+                        if self.sees(&*entity.inner, light_grid, view_area, lit_areas.as_deref()) {
                             let state = entity.inner.visible_state().unwrap();
                             if !self.environment_history.contains_key(key) {
                                 self.environment_history.insert(key, History::default());
@@ -339,23 +537,48 @@ impl Entity for Player {
                 if let Some(entry) = self.history.get(frame) {
                     const MAXIMUM_TEMPORAL_OFFSET: usize = 2;
 
-                    self.position = entry.position.map(|x| x as f64);
+                    // Past selves still push crates so that they end up in the same place every loop.
+                    let position = entry.position.map(|x| x as f64);
+                    let displacement = position - self.position;
+
+                    self.push_crates::<0>(&mut entities, light_grid, displacement.x);
+                    self.position.x = position.x;
+                    self.push_crates::<1>(&mut entities, light_grid, displacement.y);
+                    self.position.y = position.y;
+
                     self.mouse_position = entry.mouse_position.map(|x| x as f64);
+                    self.aim_mode = entry.aim_mode;
 
                     self.update_view_direction();
 
+                    // Past selves will only touch a hazard if something changed since they were
+                    // recorded, so this is left for the paradox system and elevators to deal with.
+                    if self.touches_hazard(&entities, light_grid) {
+                        self.state = PlayerState::Dead;
+                        return None;
+                    }
+
                     // Check a few previous and next frames as well to account for differences in
                     // entity insertion order.
-                    if let Some(Some((paradox_level, paradox_position))) =
-                        (frame - MAXIMUM_TEMPORAL_OFFSET..frame + MAXIMUM_TEMPORAL_OFFSET + 1)
-                            .map(|frame| self.paradox_level(frame, &entities, light_grid))
-                            .min_by(|a, b| {
-                                a.unzip()
-                                    .0
-                                    .partial_cmp(&b.unzip().0)
-                                    .unwrap_or(Ordering::Equal)
-                            })
-                    {
+                    let (paradox_frame, paradox) = (frame.saturating_sub(MAXIMUM_TEMPORAL_OFFSET)
+                        ..frame + MAXIMUM_TEMPORAL_OFFSET + 1)
+                        .map(|frame| (frame, self.paradox_level(frame, &entities, light_grid)))
+                        .min_by(|(_, a), (_, b)| {
+                            a.unzip()
+                                .0
+                                .partial_cmp(&b.unzip().0)
+                                .unwrap_or(Ordering::Equal)
+                        })
+                        .unwrap_or((frame, None));
+
+                    // Only kept while the inspector is open, since it allocates every update.
+                    self.paradox_report = if paradox_inspector::is_active() {
+                        self.paradox_report(paradox_frame, &entities, light_grid)
+                    } else {
+                        None
+                    };
+
+                    if let Some((paradox_level, paradox_position)) = paradox {
                         self.confusion += (paradox_level / Self::CONFUSION_TIME) * UPDATE_DT;
                         self.paradox_position = Some((paradox_level, paradox_position));
                     } else {
@@ -384,13 +607,17 @@ impl Entity for Player {
 
     fn update_view_area(&mut self, light_grid: &mut LightGrid) {
         self.view_area = match self.state {
-            PlayerState::Active | PlayerState::Recording => Some(light_grid.trace_light_from(
-                self.position,
-                Some(AngleRange::from_direction_and_width(
-                    self.view_direction,
-                    self.view_width,
-                )),
-            )),
+            PlayerState::Active | PlayerState::Recording => Some(
+                light_grid
+                    .trace_light_from(
+                        self.position,
+                        Some(AngleRange::from_direction_and_width(
+                            self.view_direction,
+                            self.view_width,
+                        )),
+                    )
+                    .with_radius(self.view_radius),
+            ),
             PlayerState::Dead | PlayerState::Future => None,
         };
     }
@@ -484,13 +711,23 @@ impl Entity for Player {
         }
     }
 
+    fn draw_overview(&self, canvas: &mut Canvas) {
+        let corner = self.position - self.size / 2.0;
+
+        canvas.draw_rectangle(
+            corner.x as f32,
+            corner.y as f32,
+            self.size.x as f32,
+            self.size.y as f32,
+            Color::from_hex(0xffa7cf),
+        );
+    }
+
     fn is_within_view_area(&self, light_grid: &LightGrid, view_area: &LightArea) -> bool {
         self.edges()
             .into_iter()
             .any(|line| view_area.edge_intersects_line(line))
-            || (view_area
-                .range
-                .is_none_or(|range| range.contains_offset(self.position - view_area.origin))
+            || (view_area.reaches(self.position)
                 || (self.position - view_area.origin).magnitude_squared()
                     <= Self::CONFUSION_DISTANCE_THRESHOLD.powi(2))
                 && light_grid.contains_path(view_area.origin, self.position)
@@ -539,24 +776,48 @@ impl Entity for Player {
         self.state == PlayerState::Dead
     }
 
+    fn save_runtime_state(&self) -> Vec<u8> {
+        encode_runtime_state(&(&self.history, &self.environment_history, self.view_radius))
+    }
+
+    fn load_runtime_state(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        (self.history, self.environment_history, self.view_radius) = decode_runtime_state(data)?;
+
+        Ok(())
+    }
+
     fn should_recieve_inputs(&self) -> bool {
         self.state == PlayerState::Active
     }
 
     fn key_down(&mut self, input: KeyCode) {
-        self.wasd_motion_input.key_down(input);
-        self.arrows_motion_input.key_down(input);
+        self.motion_input.input_down(input);
+        self.rotate_input.input_down(input);
     }
 
     fn key_up(&mut self, input: KeyCode) {
-        self.wasd_motion_input.key_up(input);
-        self.arrows_motion_input.key_up(input);
+        self.motion_input.input_up(input);
+        self.rotate_input.input_up(input);
+    }
+
+    fn mouse_down(&mut self, input: MouseButton, _position: Point2<f64>) {
+        self.motion_input.input_down(input);
+        self.rotate_input.input_down(input);
+    }
+
+    fn mouse_up(&mut self, input: MouseButton, _position: Point2<f64>) {
+        self.motion_input.input_up(input);
+        self.rotate_input.input_up(input);
     }
 
     fn mouse_moved(&mut self, position: Point2<f64>, _delta: Vector2<f64>) {
         self.mouse_position = position;
     }
 
+    fn analog_input(&mut self, input: AnalogInput) {
+        self.analog_input = input;
+    }
+
     fn as_player(&self) -> Option<&Player> {
         Some(self)
     }
@@ -569,49 +830,177 @@ impl Entity for Player {
 impl Player {
     fn move_along_axis<const AXIS: usize>(
         &mut self,
+        entities: &mut GuardedSlotMap<EntityKey, EntityTracker>,
         light_grid: &mut LightGrid,
         displacement: f64,
     ) {
+        self.push_crates::<AXIS>(entities, light_grid, displacement);
+
+        move_along_axis::<AXIS>(
+            &mut self.position,
+            self.size,
+            light_grid,
+            displacement,
+            Pixel::blocks_motion,
+        );
+    }
+
+    /// Pushes any crates in the way of this player moving by `displacement` far enough that they
+    /// no longer overlap, if they aren't blocked.
+    fn push_crates<const AXIS: usize>(
+        &self,
+        entities: &mut GuardedSlotMap<EntityKey, EntityTracker>,
+        light_grid: &mut LightGrid,
+        displacement: f64,
+    ) {
+        const EPSILON: f64 = 1e-6;
+
         if displacement.abs() <= f64::EPSILON {
             return;
         }
 
-        let old_position = self.position[AXIS];
-        self.position[AXIS] += displacement;
+        let mut position = self.position;
+        position[AXIS] += displacement;
 
-        let bounds = TileRect::from_rect_inclusive(self.collision_rect());
+        let min = position - self.size / 2.0;
+        let max = position + self.size / 2.0;
 
-        let mut collision = None;
+        let crates = entities
+            .iter()
+            .filter(|(_, entity)| entity.inner.as_crate().is_some())
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
 
-        for x in bounds.left()..bounds.right() + 1 {
-            for y in bounds.top()..bounds.bottom() + 1 {
-                if light_grid[point![x, y]].blocks_motion() {
-                    let axis = [x, y][AXIS];
+        for key in crates {
+            let pushable = entities[key].inner.as_crate_mut().unwrap();
 
-                    if let Some(collision) = &mut collision {
-                        if (*collision < axis) ^ (displacement > 0.0) {
-                            *collision = axis;
-                        }
-                    } else {
-                        collision = Some(axis);
+            let crate_min = pushable.position - CRATE_SIZE / 2.0;
+            let crate_max = pushable.position + CRATE_SIZE / 2.0;
+
+            let overlaps =
+                (0..2).all(|i| min[i] < crate_max[i] - EPSILON && crate_min[i] < max[i] - EPSILON);
+
+            if !overlaps || (pushable.position[AXIS] - position[AXIS]) * displacement <= 0.0 {
+                continue;
+            }
+
+            let overlap = if displacement > 0.0 {
+                max[AXIS] - crate_min[AXIS]
+            } else {
+                crate_max[AXIS] - min[AXIS]
+            };
+
+            pushable.push::<AXIS>(
+                light_grid,
+                (overlap - EPSILON).ceil() * displacement.signum(),
+            );
+        }
+    }
+}
+
+/// Moves a rectangle centered on `position` along `AXIS`, stopping it at the first pixel that
+/// `blocks` returns true for.
+pub fn move_along_axis<const AXIS: usize>(
+    position: &mut Point2<f64>,
+    size: Vector2<f64>,
+    light_grid: &LightGrid,
+    displacement: f64,
+    blocks: impl Fn(&Pixel) -> bool,
+) {
+    if displacement.abs() <= f64::EPSILON {
+        return;
+    }
+
+    let old_position = position[AXIS];
+    position[AXIS] += displacement;
+
+    let corner = *position - size / 2.0;
+    let bounds = TileRect::from_rect_inclusive(Rect::new(
+        corner.x as f32,
+        corner.y as f32,
+        size.x as f32,
+        size.y as f32,
+    ));
+
+    let mut collision = None;
+
+    for x in bounds.left()..bounds.right() + 1 {
+        for y in bounds.top()..bounds.bottom() + 1 {
+            if blocks(&light_grid[point![x, y]]) {
+                let axis = [x, y][AXIS];
+
+                if let Some(collision) = &mut collision {
+                    if (*collision < axis) ^ (displacement > 0.0) {
+                        *collision = axis;
                     }
+                } else {
+                    collision = Some(axis);
                 }
             }
         }
+    }
 
-        if let Some(mut collision) = collision {
-            if displacement < 0.0 {
-                collision += 1;
-            }
+    if let Some(mut collision) = collision {
+        if displacement < 0.0 {
+            collision += 1;
+        }
 
-            self.position[AXIS] = collision as f64;
-            self.position[AXIS] -= self.size[AXIS] * displacement.signum() / 2.0;
+        position[AXIS] = collision as f64;
+        position[AXIS] -= size[AXIS] * displacement.signum() / 2.0;
 
-            if (self.position[AXIS] < old_position) ^ (displacement < 0.0)
-                || (self.position[AXIS] - old_position).abs() > displacement.abs()
-            {
-                self.position[AXIS] = old_position;
-            }
+        if (position[AXIS] < old_position) ^ (displacement < 0.0)
+            || (position[AXIS] - old_position).abs() > displacement.abs()
+        {
+            position[AXIS] = old_position;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::Level;
+
+    #[test]
+    fn past_self_replays_first_frames_after_a_loop() {
+        let mut level = Level::new("loops_1".to_owned());
+        level.reset().unwrap();
+
+        for _ in 0..20 {
+            level.update();
+        }
+
+        // Send the active player back to the start, the same way an elevator does.
+        let key = level
+            .entities
+            .iter()
+            .find(|(_, entity)| {
+                entity
+                    .inner
+                    .as_player()
+                    .is_some_and(|player| player.state == PlayerState::Active)
+            })
+            .map(|(key, _)| key)
+            .unwrap();
+
+        let mut entity = level.entities[key].clone();
+        entity
+            .inner
+            .travel_to_beginning(&mut level.soft_reset_state[key]);
+        level.soft_reset_state.insert(entity);
+
+        level.evaluate_game_action(&GameAction::SoftReset).unwrap();
+
+        // Replaying the first few frames compares against frames before the start of the loop.
+        for _ in 0..5 {
+            level.update();
+        }
+
+        assert!(level.entities.values().any(|entity| {
+            entity
+                .inner
+                .as_player()
+                .is_some_and(|player| player.state == PlayerState::Recording)
+        }));
+    }
+}
@@ -0,0 +1,243 @@
+use std::{array, mem};
+
+use bincode::error::DecodeError;
+use macroquad::{color::Color, math::Rect, shapes, texture::Texture2D};
+use nalgebra::{Point2, Vector2, point, vector};
+use serde::{Deserialize, Serialize};
+use slotmap::SlotMap;
+
+use crate::{
+    collections::{history::FrameIndex, slot_guard::GuardedSlotMap, tile_grid::TileRect},
+    level::{
+        EntityKey,
+        entity_tracker::{
+            EntityTracker,
+            entity::{
+                Entity, EntityVisibleState, GameAction, decode_runtime_state, encode_runtime_state,
+                player,
+            },
+        },
+        light_grid::{LightArea, LightGrid, Pixel},
+    },
+    overview::Canvas,
+};
+
+pub const CRATE_SIZE: Vector2<f64> = vector![8.0, 8.0];
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct Crate {
+    pub position: Point2<f64>,
+    /// The pixels this crate replaced in the light grid, column by column, so that they can be
+    /// put back when it moves or despawns.
+    #[serde(skip)]
+    pub covered_pixels: Vec<Pixel>,
+}
+
+impl Crate {
+    pub fn collision_rect(&self) -> Rect {
+        let corner = self.position - CRATE_SIZE / 2.0;
+
+        Rect::new(
+            corner.x as f32,
+            corner.y as f32,
+            CRATE_SIZE.x as f32,
+            CRATE_SIZE.y as f32,
+        )
+    }
+
+    pub fn pixel_rect(&self) -> TileRect {
+        TileRect::from_rect_inclusive(self.collision_rect())
+    }
+
+    pub fn fill_light_grid(&self, light_grid: &mut LightGrid, pixel: Pixel) {
+        let bounds = self.pixel_rect();
+
+        for x in bounds.left()..bounds.right() + 1 {
+            for y in bounds.top()..bounds.bottom() + 1 {
+                light_grid[point![x, y]] = pixel;
+            }
+        }
+    }
+
+    /// Makes this crate solid in the light grid, remembering what it covers.
+    fn cover_light_grid(&mut self, light_grid: &mut LightGrid) {
+        let bounds = self.pixel_rect();
+
+        self.covered_pixels = (bounds.left()..bounds.right() + 1)
+            .flat_map(|x| (bounds.top()..bounds.bottom() + 1).map(move |y| point![x, y]))
+            .map(|point| light_grid[point])
+            .collect();
+
+        self.fill_light_grid(light_grid, Pixel::Solid);
+    }
+
+    /// Puts back the pixels this crate covered. Anything it doesn't remember becomes empty, since
+    /// crates can only move through empty pixels.
+    fn uncover_light_grid(&mut self, light_grid: &mut LightGrid) {
+        let bounds = self.pixel_rect();
+        let mut covered_pixels = mem::take(&mut self.covered_pixels).into_iter();
+
+        for x in bounds.left()..bounds.right() + 1 {
+            for y in bounds.top()..bounds.bottom() + 1 {
+                light_grid[point![x, y]] = covered_pixels.next().unwrap_or(Pixel::None);
+            }
+        }
+    }
+
+    /// Moves this crate by up to `displacement` along `AXIS`, stopping at anything that blocks
+    /// crates.
+    pub fn push<const AXIS: usize>(&mut self, light_grid: &mut LightGrid, displacement: f64) {
+        self.uncover_light_grid(light_grid);
+
+        player::move_along_axis::<AXIS>(
+            &mut self.position,
+            CRATE_SIZE,
+            light_grid,
+            displacement,
+            Pixel::blocks_crates,
+        );
+        // Off a whole pixel, the crate would cover an extra row or column of the light grid.
+        self.position = self.position.map(f64::round);
+
+        self.cover_light_grid(light_grid);
+    }
+
+    pub fn edges(&self) -> [[Point2<f64>; 2]; 4] {
+        let corners = [[1, 1], [-1, 1], [-1, -1], [1, -1]].map(|offset| {
+            self.position
+                + Vector2::from(offset)
+                    .map(|x| x as f64)
+                    .component_mul(&(CRATE_SIZE.map(|x| x + 1e-3) / 2.0))
+        });
+
+        array::from_fn(|i| [corners[i], corners[(i + 1) % corners.len()]])
+    }
+}
+
+#[typetag::serde]
+impl Entity for Crate {
+    fn update(
+        &mut self,
+        _frame: FrameIndex,
+        _entities: GuardedSlotMap<EntityKey, EntityTracker>,
+        light_grid: &mut LightGrid,
+        _initial_state: &mut SlotMap<EntityKey, EntityTracker>,
+    ) -> Option<GameAction> {
+        if self.covered_pixels.is_empty() {
+            self.cover_light_grid(light_grid);
+
+            return None;
+        }
+
+        // Doors can overwrite our pixels as they move.
+        let bounds = self.pixel_rect();
+
+        if (bounds.left()..bounds.right() + 1).any(|x| {
+            (bounds.top()..bounds.bottom() + 1).any(|y| !light_grid[point![x, y]].blocks_light())
+        }) {
+            self.fill_light_grid(light_grid, Pixel::Solid);
+        }
+
+        None
+    }
+
+    fn spawn(&mut self, _key: EntityKey, _entities: &mut SlotMap<EntityKey, EntityTracker>) {
+        // Crates placed before positions were snapped may be off a whole pixel.
+        self.position = self.position.map(f64::round);
+    }
+
+    fn despawn(&mut self, light_grid: &mut LightGrid) {
+        self.uncover_light_grid(light_grid);
+    }
+
+    fn save_runtime_state(&self) -> Vec<u8> {
+        encode_runtime_state(&self.covered_pixels)
+    }
+
+    fn load_runtime_state(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        self.covered_pixels = decode_runtime_state(data)?;
+
+        Ok(())
+    }
+
+    fn draw_wall(&mut self, _texture_atlas: &Texture2D) {
+        let rect = self.collision_rect();
+
+        shapes::draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_hex(0x6b4a2f));
+        shapes::draw_rectangle(
+            rect.x + 1.0,
+            rect.y + 1.0,
+            rect.w - 2.0,
+            rect.h - 2.0,
+            Color::from_hex(0x9c6d40),
+        );
+        shapes::draw_line(
+            rect.x + 1.0,
+            rect.y + 1.0,
+            rect.x + rect.w - 1.0,
+            rect.y + rect.h - 1.0,
+            1.0,
+            Color::from_hex(0x6b4a2f),
+        );
+    }
+
+    fn draw_overview(&self, canvas: &mut Canvas) {
+        let rect = self.collision_rect();
+
+        canvas.draw_rectangle(rect.x, rect.y, rect.w, rect.h, Color::from_hex(0x6b4a2f));
+        canvas.draw_rectangle(
+            rect.x + 1.0,
+            rect.y + 1.0,
+            rect.w - 2.0,
+            rect.h - 2.0,
+            Color::from_hex(0x9c6d40),
+        );
+        canvas.draw_line(
+            rect.x + 1.0,
+            rect.y + 1.0,
+            rect.x + rect.w - 1.0,
+            rect.y + rect.h - 1.0,
+            1.0,
+            Color::from_hex(0x6b4a2f),
+        );
+    }
+
+    fn collision_rect(&self) -> Option<TileRect> {
+        Some(self.pixel_rect())
+    }
+
+    fn is_within_view_area(&self, light_grid: &LightGrid, view_area: &LightArea) -> bool {
+        self.edges()
+            .into_iter()
+            .any(|line| view_area.edge_intersects_line(line))
+            || view_area.sees_point(light_grid, self.position)
+    }
+
+    fn visible_state(&self) -> Option<EntityVisibleState> {
+        Some(EntityVisibleState::new(self.position, 0))
+    }
+
+    fn position(&self) -> Point2<f64> {
+        self.position
+    }
+
+    fn position_mut(&mut self) -> Option<&mut Point2<f64>> {
+        Some(&mut self.position)
+    }
+
+    fn duplicate(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
+
+    fn should_recieve_inputs(&self) -> bool {
+        false
+    }
+
+    fn as_crate(&self) -> Option<&Crate> {
+        Some(self)
+    }
+
+    fn as_crate_mut(&mut self) -> Option<&mut Crate> {
+        Some(self)
+    }
+}
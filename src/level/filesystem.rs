@@ -5,6 +5,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use bincode::error::DecodeError;
 use include_dir::{Dir, include_dir};
 
 #[derive(Clone)]
@@ -14,6 +15,7 @@ pub enum FileSystem {
 }
 
 pub const LEVELS_DIRECTORY: &str = "resources/levels";
+pub const SAVES_DIRECTORY: &str = "saves";
 pub const STORED_LEVELS: Dir = include_dir!("resources/levels");
 
 impl Default for FileSystem {
@@ -62,10 +64,30 @@ impl FileSystem {
     }
 }
 
+/// Reads a file from the saves directory, which is kept separate from the levels.
+pub fn load_save_file(name: &str) -> io::Result<Vec<u8>> {
+    if cfg!(target_family = "wasm") {
+        return Err(io::Error::from(io::ErrorKind::Unsupported));
+    }
+
+    fs::read(Path::new(SAVES_DIRECTORY).join(name))
+}
+
+/// Writes a file to the saves directory, creating it if needed.
+pub fn write_save_file(name: &str, contents: &[u8]) -> io::Result<()> {
+    if cfg!(target_family = "wasm") {
+        return Err(io::Error::from(io::ErrorKind::Unsupported));
+    }
+
+    fs::create_dir_all(SAVES_DIRECTORY)?;
+    fs::write(Path::new(SAVES_DIRECTORY).join(name), contents)
+}
+
 #[derive(Debug)]
 pub enum LoadLevelError {
     NoSuchLevel,
     IoError(io::Error),
+    DecodeError(DecodeError),
 }
 
 impl Display for LoadLevelError {
@@ -73,6 +95,7 @@ impl Display for LoadLevelError {
         match self {
             LoadLevelError::NoSuchLevel => write!(f, "No such level to load",),
             LoadLevelError::IoError(error) => write!(f, "{error}"),
+            LoadLevelError::DecodeError(error) => write!(f, "Level data is corrupt: {error}"),
         }
     }
 }
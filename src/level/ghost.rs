@@ -0,0 +1,233 @@
+use std::{io, mem};
+
+use macroquad::{color::Color, shapes};
+use nalgebra::{Point2, Vector2, point, vector};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collections::history::{FrameIndex, History},
+    input::AimMode,
+    level::{
+        Level,
+        entity_tracker::entity::player::{PlayerHistoryEntry, PlayerState},
+        filesystem,
+        level_stats::LevelStats,
+    },
+};
+
+pub const GHOST_COLOR: Color = Color::new(0.65, 0.85, 1.0, 0.35);
+/// The same as a player's.
+pub const GHOST_SIZE: Vector2<f32> = vector![6.0, 6.0];
+
+/// Positions are stored in fractions of this many pixels.
+const GHOST_PRECISION: f32 = 16.0;
+
+pub fn ghost_file(level: &str) -> String {
+    format!("{level}.ghost")
+}
+
+/// The best completion of the current level, replayed alongside the player. It isn't an entity,
+/// so nothing can see it and it never takes part in paradox detection.
+#[derive(Clone, Debug)]
+pub struct Ghost {
+    pub active: bool,
+    /// Indexed by frames since the level was started, across every loop.
+    pub best: Option<History<PlayerHistoryEntry>>,
+    /// The current attempt, which replaces `best` if it's faster.
+    pub recording: History<PlayerHistoryEntry>,
+}
+
+impl Default for Ghost {
+    fn default() -> Self {
+        Self {
+            active: true,
+            best: None,
+            recording: History::default(),
+        }
+    }
+}
+
+/// A ghost as it's saved. Each frame is stored as the change from the last one, and repeated
+/// changes are merged, so that the varints bincode uses stay small.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+struct GhostFile {
+    start: FrameIndex,
+    /// How many frames in a row had the same change in position and mouse position.
+    runs: Vec<(u32, [i32; 4], AimMode)>,
+}
+
+impl GhostFile {
+    fn encode(history: &History<PlayerHistoryEntry>) -> GhostFile {
+        let range = history.range();
+        let mut runs: Vec<(u32, [i32; 4], AimMode)> = Vec::new();
+        let mut last = [0; 4];
+        let mut last_entry = None;
+
+        for frame in range.clone() {
+            // Gaps repeat the frame before them.
+            let Some(entry) = history.get(frame).or(last_entry) else {
+                continue;
+            };
+            last_entry = Some(entry);
+
+            let quantized = [
+                entry.position.x,
+                entry.position.y,
+                entry.mouse_position.x,
+                entry.mouse_position.y,
+            ]
+            .map(|x| (x * GHOST_PRECISION).round() as i32);
+
+            let delta = [0, 1, 2, 3].map(|i| quantized[i] - last[i]);
+            last = quantized;
+
+            match runs.last_mut() {
+                Some((count, last_delta, aim_mode))
+                    if *last_delta == delta && *aim_mode == entry.aim_mode =>
+                {
+                    *count += 1;
+                }
+                _ => runs.push((1, delta, entry.aim_mode)),
+            }
+        }
+
+        GhostFile {
+            start: range.start,
+            runs,
+        }
+    }
+
+    fn decode(&self) -> History<PlayerHistoryEntry> {
+        let mut history = History::default();
+        let mut frame = self.start;
+        let mut current = [0; 4];
+
+        for &(count, delta, aim_mode) in &self.runs {
+            for _ in 0..count {
+                current = [0, 1, 2, 3].map(|i| current[i] + delta[i]);
+
+                let [x, y, mouse_x, mouse_y] = current.map(|x| x as f32 / GHOST_PRECISION);
+
+                history.try_insert(
+                    frame,
+                    PlayerHistoryEntry {
+                        position: point![x, y],
+                        mouse_position: point![mouse_x, mouse_y],
+                        aim_mode,
+                    },
+                );
+                frame += 1;
+            }
+        }
+
+        history
+    }
+}
+
+impl Level {
+    pub fn load_ghost(&mut self) {
+        self.ghost.best = filesystem::load_save_file(&ghost_file(&self.level_name))
+            .ok()
+            .and_then(|data| {
+                bincode::serde::decode_from_slice::<GhostFile, _>(
+                    &data,
+                    bincode::config::standard(),
+                )
+                .ok()
+            })
+            .map(|(file, _)| file.decode());
+    }
+
+    pub fn record_ghost_frame(&mut self) {
+        for &key in &self.input_readers {
+            if let Some(entity) = self.entities.get(key)
+                && let Some(player) = entity.inner.as_player()
+                && player.state == PlayerState::Active
+            {
+                self.ghost
+                    .recording
+                    .try_insert(self.stats.frames, player.get_history_entry());
+                break;
+            }
+        }
+    }
+
+    /// Saves `recording` as the ghost for `level` if it's faster than the best completion so far.
+    /// Must be called before the completion is added to the level's record.
+    pub fn save_ghost_if_best(
+        &mut self,
+        level: &str,
+        recording: History<PlayerHistoryEntry>,
+        stats: LevelStats,
+    ) {
+        let is_best = self
+            .progress
+            .level_records
+            .get(level)
+            .is_none_or(|record| record.completions == 0 || stats.frames < record.best.frames);
+
+        if !is_best || recording.records() == 0 {
+            return;
+        }
+
+        let data = bincode::serde::encode_to_vec(
+            GhostFile::encode(&recording),
+            bincode::config::standard(),
+        )
+        .unwrap();
+
+        if let Err(error) = filesystem::write_save_file(&ghost_file(level), &data)
+            && error.kind() != io::ErrorKind::Unsupported
+        {
+            self.show_notice(format!("Couldn't save ghost: {error}"));
+        }
+
+        if level == self.level_name {
+            self.ghost.best = Some(recording);
+        }
+    }
+
+    pub fn take_ghost_recording(&mut self) -> History<PlayerHistoryEntry> {
+        mem::take(&mut self.ghost.recording)
+    }
+
+    pub fn draw_ghost(&self) {
+        if !self.ghost.active {
+            return;
+        }
+
+        // Frames are recorded before `update_stats` counts them, so the last update's is one behind.
+        let Some(best) = &self.ghost.best else {
+            return;
+        };
+        let Some(frame) = self.stats.frames.checked_sub(1) else {
+            return;
+        };
+        let Some(entry) = best.get(frame) else {
+            return;
+        };
+
+        // Drawn between the last two updates, the same as entities.
+        let previous = frame
+            .checked_sub(1)
+            .and_then(|frame| best.get(frame))
+            .unwrap_or(entry);
+        let tick_fraction = self.tick_fraction as f32;
+
+        let position = previous.position.lerp(&entry.position, tick_fraction);
+        let mouse_position = previous
+            .mouse_position
+            .lerp(&entry.mouse_position, tick_fraction);
+        let corner = position - GHOST_SIZE / 2.0;
+
+        shapes::draw_rectangle(corner.x, corner.y, GHOST_SIZE.x, GHOST_SIZE.y, GHOST_COLOR);
+
+        let direction = mouse_position - position;
+
+        if let Some(direction) = direction.try_normalize(f32::EPSILON) {
+            let end: Point2<f32> = position + direction * GHOST_SIZE.x * 1.5;
+
+            shapes::draw_line(position.x, position.y, end.x, end.y, 1.0, GHOST_COLOR);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use macroquad::{
+    color::{Color, colors},
+    shapes,
+    text::{self, TextParams},
+};
+use nalgebra::{Point2, point};
+
+use crate::{
+    collections::history::FrameIndex,
+    level::{Level, UPDATE_DT, UPDATE_TPS},
+};
+
+pub const TEXT_LINE_HEIGHT: f32 = 6.0;
+
+/// How many seconds a notice stays on screen.
+pub const NOTICE_DURATION: f64 = 2.0;
+
+/// Formats a number of update ticks as minutes, seconds and hundredths.
+pub fn format_time(ticks: FrameIndex) -> String {
+    let seconds = ticks as f64 / UPDATE_TPS as f64;
+
+    format!("{}:{:05.2}", (seconds / 60.0).floor(), seconds % 60.0)
+}
+
+/// Draws a line of debug sized text on a dark background, with `start` on the baseline.
+pub fn draw_text_line(text: &str, start: Point2<f32>, color: Color) {
+    let width = text::measure_text(text, None, 8, 1.0).width;
+
+    shapes::draw_rectangle(
+        start.x,
+        start.y - 5.0,
+        width,
+        TEXT_LINE_HEIGHT,
+        Color::new(0.0, 0.0, 0.0, 0.75),
+    );
+
+    text::draw_text_ex(
+        text,
+        start.x,
+        start.y,
+        TextParams {
+            font_size: 16,
+            font_scale: 0.5,
+            color,
+            ..Default::default()
+        },
+    );
+}
+
+impl Level {
+    pub fn show_notice(&mut self, text: String) {
+        self.notice = Some((text, 0.0));
+    }
+
+    pub fn update_notice(&mut self) {
+        self.age_notice(UPDATE_DT);
+    }
+
+    /// Ages the notice by `dt` seconds, for when the game isn't updating, such as while paused.
+    pub fn age_notice(&mut self, dt: f64) {
+        if let Some((_, age)) = &mut self.notice {
+            *age += dt;
+
+            if *age > NOTICE_DURATION {
+                self.notice = None;
+            }
+        }
+    }
+
+    pub fn draw_notice(&self) {
+        let Some((text, _)) = &self.notice else {
+            return;
+        };
+
+        let screen_rect = crate::screen_rect();
+        let width = text::measure_text(text, None, 8, 1.0).width;
+
+        draw_text_line(
+            text,
+            point![
+                screen_rect.x + (screen_rect.w - width) / 2.0,
+                screen_rect.y + screen_rect.h - 4.0
+            ],
+            colors::WHITE,
+        );
+    }
+}
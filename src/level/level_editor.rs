@@ -13,19 +13,25 @@ use slotmap::SlotMap;
 
 use crate::{
     collections::tile_grid::{TileGrid, TileIndexOffset},
+    input::{self, Action},
     level::{
-        EntityKey, Level, TILE_SIZE,
+        EntityKey, Level, LevelGraphics, TILE_SIZE,
         entity_tracker::{
             EntityTracker,
             entity::{
                 Entity, GameAction,
                 button::Button,
                 elevator::{Elevator, ElevatorDirection},
+                lamp::{DEFAULT_LAMP_RADIUS, Lamp},
+                laser::{Laser, LaserKind},
                 logic_gate::{LogicGate, LogicGateDirection, LogicGateKind},
                 player::Player,
+                pushable_crate::Crate,
             },
         },
+        metadata::{LevelMetadata, MetadataField},
         tile::{self, TILE_KINDS, Tile},
+        time_control::{TIME_SCALES, TimeControl},
     },
 };
 
@@ -74,6 +80,11 @@ pub enum Command {
     Clear,
     Shift(TileIndexOffset),
     Wire(Option<EntityKey>),
+    Metadata(Option<(MetadataField, String)>),
+    ViewRadius(Option<f64>),
+    Speed(f64),
+    Pause,
+    Step(usize),
 }
 
 impl Command {
@@ -87,6 +98,11 @@ impl Command {
             Command::Clear => false,
             Command::Shift(_) => false,
             Command::Wire(_) => true,
+            Command::Metadata(_) => false,
+            Command::ViewRadius(_) => true,
+            Command::Speed(_) => false,
+            Command::Pause => false,
+            Command::Step(_) => false,
         }
     }
 
@@ -100,6 +116,11 @@ impl Command {
             Command::Clear => true,
             Command::Shift(_) => true,
             Command::Wire(_) => false,
+            Command::Metadata(_) => true,
+            Command::ViewRadius(_) => false,
+            Command::Speed(_) => true,
+            Command::Pause => true,
+            Command::Step(_) => true,
         }
     }
 }
@@ -188,6 +209,32 @@ impl FromStr for Command {
                         animation_state: 0,
                     }),
                     Some(&"button") => Box::new(Button::default()),
+                    Some(&"crate") => Box::new(Crate::default()),
+                    Some(&"lamp") => Box::new(Lamp::new(
+                        point![0.0, 0.0],
+                        match words.get(2) {
+                            Some(radius) => match radius.parse::<f64>() {
+                                Ok(radius) if radius > 0.0 => radius,
+                                _ => return Err(()),
+                            },
+                            None => DEFAULT_LAMP_RADIUS,
+                        },
+                    )),
+                    Some(&"laser") => Box::new(Laser::new(
+                        point![0.0, 0.0],
+                        match words.get(2) {
+                            Some(&"east") => ElevatorDirection::East,
+                            Some(&"north") => ElevatorDirection::North,
+                            Some(&"west") => ElevatorDirection::West,
+                            Some(&"south") => ElevatorDirection::South,
+                            _ => return Err(()),
+                        },
+                        match words.get(3) {
+                            Some(&"lethal") | None => LaserKind::Lethal,
+                            Some(&"tripwire") => LaserKind::Tripwire,
+                            _ => return Err(()),
+                        },
+                    )),
                     _ => return Err(()),
                 };
 
@@ -208,6 +255,34 @@ impl FromStr for Command {
                 Ok(Command::Shift(vector![get_axis(1)?, get_axis(2)?]))
             }
             Some(&"wire") => Ok(Command::Wire(None)),
+            Some(&"meta") => match words.get(1) {
+                Some(field) => Ok(Command::Metadata(Some((
+                    field.parse()?,
+                    words[2..].join(" "),
+                )))),
+                None => Ok(Command::Metadata(None)),
+            },
+            Some(&"view") => match words.get(1) {
+                Some(radius) => match radius.parse::<f64>() {
+                    Ok(radius) if radius > 0.0 => Ok(Command::ViewRadius(Some(radius))),
+                    _ => Err(()),
+                },
+                None => Ok(Command::ViewRadius(None)),
+            },
+            Some(&"speed") => match words.get(1) {
+                Some(scale) => match scale.parse::<f64>() {
+                    Ok(scale) if scale > 0.0 && scale <= TIME_SCALES[TIME_SCALES.len() - 1] => {
+                        Ok(Command::Speed(scale))
+                    }
+                    _ => Err(()),
+                },
+                None => Ok(Command::Speed(1.0)),
+            },
+            Some(&"pause") => Ok(Command::Pause),
+            Some(&"step") => match words.get(1) {
+                Some(steps) => steps.parse().map(Command::Step).map_err(|_| ()),
+                None => Ok(Command::Step(1)),
+            },
             _ => Err(()),
         }
     }
@@ -307,6 +382,22 @@ impl Level {
                     color,
                 );
             }
+
+            if let Some(radius) = entity
+                .inner
+                .as_player()
+                .and_then(|player| player.view_radius)
+            {
+                let position = entity.inner.position();
+
+                shapes::draw_circle_lines(
+                    position.x as f32,
+                    position.y as f32,
+                    radius as f32,
+                    1.0,
+                    color,
+                );
+            }
         }
 
         let screen_rect = crate::screen_rect();
@@ -413,6 +504,7 @@ impl Level {
                                             Ok(()) => {
                                                 self.level_data = Some(level_data);
                                                 self.level_name = level_name;
+                                                self.level_graph = None;
                                             }
                                             Err(error) => {
                                                 self.editor.command_input = format!("{error}");
@@ -448,6 +540,44 @@ impl Level {
                                     self.level_data = None;
                                     self.tile_grid = TileGrid::default();
                                     self.hard_reset_state = SlotMap::default();
+                                    self.metadata = LevelMetadata::default();
+                                }
+                                Command::Metadata(None) => {
+                                    self.editor.command_input = format!("{}", self.metadata);
+                                }
+                                Command::Metadata(Some((field, value))) => {
+                                    match self.metadata.set(field, &value) {
+                                        Ok(()) => {
+                                            self.editor.command_input =
+                                                format!("{}", self.metadata);
+                                        }
+                                        Err(()) => {
+                                            self.editor.command_input =
+                                                format!("Invalid number: {value}");
+                                        }
+                                    }
+                                }
+                                Command::Speed(scale) => {
+                                    self.set_time_control(TimeControl {
+                                        scale,
+                                        ..Default::default()
+                                    });
+                                    self.editor.command_input = self.time_control_text();
+                                }
+                                Command::Pause => {
+                                    let mut time_control = self.time_control;
+                                    time_control.paused ^= true;
+                                    time_control.steps = 0;
+
+                                    self.set_time_control(time_control);
+                                    self.editor.command_input = self.time_control_text();
+                                }
+                                Command::Step(steps) => {
+                                    let mut time_control = self.time_control;
+                                    time_control.step(steps);
+
+                                    self.set_time_control(time_control);
+                                    self.editor.command_input = self.time_control_text();
                                 }
                                 Command::Shift(offset) => {
                                     self.tile_grid.shift(offset);
@@ -526,8 +656,10 @@ impl Level {
     }
 
     pub fn level_editor_key_down(&mut self, input: KeyCode) {
+        let bindings = input::key_bindings().clone();
+
         match input {
-            KeyCode::Escape => {
+            _ if bindings.is_bound(Action::EditorCancel, input) => {
                 self.editor.command_input.clear();
                 self.editor.cursor = None;
 
@@ -546,7 +678,7 @@ impl Level {
 
         if let Some(cursor) = &mut self.editor.cursor {
             match input {
-                KeyCode::Left => loop {
+                _ if bindings.is_bound(Action::EditorCursorLeft, input) => loop {
                     *cursor = self
                         .editor
                         .command_input
@@ -559,7 +691,7 @@ impl Level {
                         break;
                     }
                 },
-                KeyCode::Right => loop {
+                _ if bindings.is_bound(Action::EditorCursorRight, input) => loop {
                     *cursor = self
                         .editor
                         .command_input
@@ -572,13 +704,13 @@ impl Level {
                         break;
                     }
                 },
-                KeyCode::Home => {
+                _ if bindings.is_bound(Action::EditorCursorStart, input) => {
                     *cursor = 0;
                 }
-                KeyCode::End => {
+                _ if bindings.is_bound(Action::EditorCursorEnd, input) => {
                     *cursor = self.editor.command_input.len();
                 }
-                KeyCode::Up => {
+                _ if bindings.is_bound(Action::EditorHistoryPrevious, input) => {
                     self.editor.command_input_history_index =
                         self.editor.command_input_history_index.saturating_sub(1);
                     self.editor.command_input = self
@@ -589,7 +721,7 @@ impl Level {
                         .unwrap_or_default();
                     self.editor.cursor = Some(self.editor.command_input.len());
                 }
-                KeyCode::Down => {
+                _ if bindings.is_bound(Action::EditorHistoryNext, input) => {
                     self.editor.command_input_history_index = self
                         .editor
                         .command_input_history_index
@@ -680,6 +812,15 @@ impl Level {
                 }
                 _ => (),
             },
+            &mut Some(Command::ViewRadius(radius)) if input == MouseButton::Right => {
+                if let Some(player) = self
+                    .editor
+                    .selected_entity
+                    .and_then(|selection| self.hard_reset_state[selection].inner.as_player_mut())
+                {
+                    player.view_radius = radius;
+                }
+            }
             _ => (),
         }
     }
@@ -722,6 +863,11 @@ impl Level {
     }
 
     pub fn level_editor_draw_level_contents(&mut self) {
+        let texture_atlas = &self
+            .graphics
+            .get_or_insert_with(LevelGraphics::new)
+            .texture_atlas;
+
         // Non-wall Tiles
         {
             let tile_kinds = tile::TILE_KINDS.lock().unwrap();
@@ -740,7 +886,7 @@ impl Level {
                     }
 
                     texture::draw_texture_ex(
-                        &self.texture_atlas,
+                        texture_atlas,
                         x as f32 * TILE_SIZE as f32,
                         y as f32 * TILE_SIZE as f32,
                         colors::WHITE,
@@ -755,7 +901,7 @@ impl Level {
 
         // Floor like entities
         for (_, entity) in &mut self.hard_reset_state {
-            entity.inner.draw_floor(&self.texture_atlas);
+            entity.inner.draw_floor(texture_atlas);
         }
 
         // Wall Tiles
@@ -776,7 +922,7 @@ impl Level {
                     }
 
                     texture::draw_texture_ex(
-                        &self.texture_atlas,
+                        texture_atlas,
                         x as f32 * TILE_SIZE as f32,
                         y as f32 * TILE_SIZE as f32,
                         colors::WHITE,
@@ -791,35 +937,35 @@ impl Level {
 
         // Wall like entities
         for (_, entity) in &mut self.hard_reset_state {
-            entity.inner.draw_wall(&self.texture_atlas);
+            entity.inner.draw_wall(texture_atlas);
         }
 
         // Vision occluded entities
         for (_, entity) in &mut self.hard_reset_state {
-            entity.inner.draw_back(&self.texture_atlas);
+            entity.inner.draw_back(texture_atlas);
         }
 
         // Always visible entities
         for (_, entity) in &mut self.hard_reset_state {
-            entity.inner.draw_effect_back(&self.texture_atlas);
+            entity.inner.draw_effect_back(texture_atlas);
         }
 
         Self::draw_wires(&self.hard_reset_state, Some(colors::MAROON));
 
         for (_, entity) in &mut self.hard_reset_state {
-            entity.inner.draw_overlay_back(&self.texture_atlas);
+            entity.inner.draw_overlay_back(texture_atlas);
         }
 
         for (_, entity) in &mut self.hard_reset_state {
-            entity.inner.draw_front(&self.texture_atlas);
+            entity.inner.draw_front(texture_atlas);
         }
 
         for (_, entity) in &mut self.hard_reset_state {
-            entity.inner.draw_effect_front(&self.texture_atlas);
+            entity.inner.draw_effect_front(texture_atlas);
         }
 
         for (_, entity) in &mut self.hard_reset_state {
-            entity.inner.draw_overlay_front(&self.texture_atlas);
+            entity.inner.draw_overlay_front(texture_atlas);
         }
     }
 }
@@ -0,0 +1,156 @@
+use macroquad::{color::colors, input::KeyCode, shapes};
+use nalgebra::point;
+
+use crate::{
+    input::{self, Action},
+    level::{
+        Level,
+        hud::{TEXT_LINE_HEIGHT, draw_text_line},
+        progress::LevelGraph,
+    },
+};
+
+#[derive(Clone, Default, Debug)]
+pub struct LevelSelect {
+    pub active: bool,
+    pub selected: usize,
+}
+
+impl Level {
+    pub fn open_level_select(&mut self) {
+        // Levels may have been saved from the editor since the graph was last built.
+        self.level_graph = Some(LevelGraph::new(&self.filesystem));
+
+        let level_name = self.level_name.clone();
+
+        self.level_select.active = true;
+        self.level_select.selected = self
+            .level_graph()
+            .levels
+            .iter()
+            .position(|node| node.name == level_name)
+            .unwrap_or(0);
+    }
+
+    pub fn level_select_key_down(&mut self, input: KeyCode) {
+        let Some(graph) = &self.level_graph else {
+            return;
+        };
+        let level_count = graph.levels.len();
+        let bindings = input::key_bindings().clone();
+
+        match input {
+            _ if bindings.is_bound(Action::MoveUp, input) => {
+                self.level_select.selected = self.level_select.selected.saturating_sub(1);
+            }
+            _ if bindings.is_bound(Action::MoveDown, input) => {
+                self.level_select.selected =
+                    (self.level_select.selected + 1).min(level_count.saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Space => {
+                let Some(node) = graph.levels.get(self.level_select.selected) else {
+                    return;
+                };
+
+                if !self.progress.is_unlocked(graph, &node.name) {
+                    return;
+                }
+
+                let level_name = node.name.clone();
+
+                self.level_select.active = false;
+                self.the_end = None;
+
+                let old_level_name = self.level_name.clone();
+                let old_level_data = self.level_data.take();
+                self.level_name = level_name.clone();
+
+                match self.reset() {
+                    Ok(()) => {
+                        self.restart_speedrun();
+                        self.step_at_level_start();
+                        self.record_level_reached(level_name);
+                    }
+                    Err(error) => {
+                        self.level_name = old_level_name;
+                        self.level_data = old_level_data;
+
+                        self.show_notice(format!("Couldn't load {level_name}: {error}"));
+                    }
+                }
+            }
+            _ if input == KeyCode::Escape || bindings.is_bound(Action::LevelSelect, input) => {
+                self.level_select.active = false;
+            }
+            _ => (),
+        }
+    }
+
+    pub fn draw_level_select(&self) {
+        let Some(graph) = &self.level_graph else {
+            return;
+        };
+
+        let screen_rect = crate::screen_rect();
+
+        shapes::draw_rectangle(
+            screen_rect.x,
+            screen_rect.y,
+            screen_rect.w,
+            screen_rect.h,
+            colors::BLACK,
+        );
+
+        let mut start = point![screen_rect.x + 8.0, screen_rect.y + 12.0];
+
+        draw_text_line("Level Select", start, colors::WHITE);
+        start.y += TEXT_LINE_HEIGHT * 2.0;
+
+        for (i, node) in graph.levels.iter().enumerate() {
+            let unlocked = self.progress.is_unlocked(graph, &node.name);
+            let completed = self.progress.completed_levels.contains(&node.name);
+
+            let cursor = if i == self.level_select.selected {
+                ">"
+            } else {
+                " "
+            };
+            let status = if completed {
+                " (completed)"
+            } else if !unlocked {
+                " (locked)"
+            } else {
+                ""
+            };
+
+            let color = if !unlocked {
+                colors::DARKGRAY
+            } else if node.name == self.level_name {
+                colors::YELLOW
+            } else {
+                colors::WHITE
+            };
+
+            let name = if node.title.is_empty() {
+                &node.name
+            } else {
+                &node.title
+            };
+
+            draw_text_line(
+                &format!("{cursor} {:>2} {name}{status}", node.depth + 1),
+                start,
+                color,
+            );
+            start.y += TEXT_LINE_HEIGHT;
+        }
+
+        start.y += TEXT_LINE_HEIGHT;
+
+        draw_text_line(
+            "Up and down to choose, enter to play, tab to close",
+            start,
+            colors::GRAY,
+        );
+    }
+}
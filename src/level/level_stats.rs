@@ -0,0 +1,170 @@
+use macroquad::{color::colors, text};
+use nalgebra::point;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collections::history::{FrameIndex, History},
+    level::{
+        Level, UPDATE_DT,
+        hud::{TEXT_LINE_HEIGHT, draw_text_line, format_time},
+    },
+};
+
+/// How many seconds the summary card stays on screen after leaving a level.
+pub const SUMMARY_CARD_DURATION: f64 = 6.0;
+
+/// How a single attempt at a level went, from entering it to leaving through an exit.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize, Debug)]
+pub struct LevelStats {
+    /// How many times a loop elevator sent the player back.
+    pub loops: u32,
+    /// Across every loop.
+    pub frames: FrameIndex,
+    /// The most confused the player got, from 0 to 1.
+    pub peak_confusion: f64,
+    pub deaths: u32,
+}
+
+impl LevelStats {
+    pub fn time_text(&self) -> String {
+        format_time(self.frames)
+    }
+}
+
+/// The best of every completion of a level, kept in the progress save.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize, Debug)]
+pub struct LevelRecord {
+    pub completions: u32,
+    /// Each field is the best across all completions, so they may come from different attempts.
+    pub best: LevelStats,
+}
+
+impl LevelRecord {
+    pub fn add(&mut self, stats: LevelStats) {
+        self.best = if self.completions == 0 {
+            stats
+        } else {
+            LevelStats {
+                loops: self.best.loops.min(stats.loops),
+                frames: self.best.frames.min(stats.frames),
+                peak_confusion: self.best.peak_confusion.min(stats.peak_confusion),
+                deaths: self.best.deaths.min(stats.deaths),
+            }
+        };
+
+        self.completions += 1;
+    }
+}
+
+/// Shown after an exit elevator takes the player to the next level.
+#[derive(Clone, Debug)]
+pub struct SummaryCard {
+    pub title: String,
+    pub stats: LevelStats,
+    /// From before this completion was added, if the level had been completed before.
+    pub previous_record: Option<LevelRecord>,
+    pub age: f64,
+}
+
+impl Level {
+    /// Starts counting again, along with the ghost recording.
+    pub fn reset_stats(&mut self) {
+        self.stats = LevelStats::default();
+        self.ghost.recording = History::default();
+    }
+
+    pub fn update_stats(&mut self) {
+        self.stats.frames += 1;
+
+        for &key in &self.input_readers {
+            if let Some(entity) = self.entities.get(key)
+                && let Some(player) = entity.inner.as_player()
+            {
+                self.stats.peak_confusion = self.stats.peak_confusion.max(player.confusion);
+            }
+        }
+
+        if let Some(summary) = &mut self.summary_card {
+            summary.age += UPDATE_DT;
+
+            if summary.age > SUMMARY_CARD_DURATION {
+                self.summary_card = None;
+            }
+        }
+    }
+
+    /// Adds a completion of `level` to its record and shows the summary card.
+    pub fn record_level_stats(&mut self, level: String, title: String, stats: LevelStats) {
+        let record = self.progress.level_records.entry(level).or_default();
+        let previous_record = (record.completions > 0).then_some(*record);
+
+        record.add(stats);
+
+        self.summary_card = Some(SummaryCard {
+            title,
+            stats,
+            previous_record,
+            age: 0.0,
+        });
+    }
+
+    pub fn draw_summary_card(&self) {
+        let Some(summary) = &self.summary_card else {
+            return;
+        };
+
+        let stats = summary.stats;
+        let best = summary.previous_record.map(|record| record.best);
+        let percent = |confusion: f64| format!("{:.0}%", confusion * 100.0);
+
+        let rows = [
+            (
+                "Loops",
+                stats.loops.to_string(),
+                best.map(|best| (stats.loops < best.loops, best.loops.to_string())),
+            ),
+            (
+                "Time",
+                stats.time_text(),
+                best.map(|best| (stats.frames < best.frames, best.time_text())),
+            ),
+            (
+                "Peak confusion",
+                percent(stats.peak_confusion),
+                best.map(|best| {
+                    (
+                        stats.peak_confusion < best.peak_confusion,
+                        percent(best.peak_confusion),
+                    )
+                }),
+            ),
+            (
+                "Deaths",
+                stats.deaths.to_string(),
+                best.map(|best| (stats.deaths < best.deaths, best.deaths.to_string())),
+            ),
+        ];
+
+        let mut lines = vec![(format!("{} complete", summary.title), colors::WHITE)];
+
+        for (label, value, best) in rows {
+            lines.push(match best {
+                Some((true, _)) => (format!("{label}: {value} (new best)"), colors::YELLOW),
+                Some((false, best)) => {
+                    (format!("{label}: {value} (best {best})"), colors::LIGHTGRAY)
+                }
+                None => (format!("{label}: {value}"), colors::LIGHTGRAY),
+            });
+        }
+
+        let screen_rect = crate::screen_rect();
+        let mut y = screen_rect.y + screen_rect.h * 0.6;
+
+        for (line, color) in lines {
+            let width = text::measure_text(&line, None, 8, 1.0).width;
+
+            draw_text_line(&line, point![-width / 2.0, y], color);
+            y += TEXT_LINE_HEIGHT;
+        }
+    }
+}
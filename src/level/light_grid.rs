@@ -1,6 +1,7 @@
 use std::{
     array,
     cmp::Ordering,
+    f64::consts::{PI, TAU},
     mem,
     ops::{Index, IndexMut},
 };
@@ -17,13 +18,16 @@ use macroquad::{
 use nalgebra::{Point2, Scalar, UnitComplex, UnitVector2, Vector2, point, vector};
 use serde::{Deserialize, Serialize};
 
-use crate::collections::tile_grid::{Empty, TileGrid, TileIndex};
+use crate::collections::tile_grid::{Empty, TileGrid, TileIndex, TileRect};
 
 #[derive(Clone, Default, Debug)]
 pub struct LightGrid {
     grid: TileGrid<Pixel>,
-    updated: bool,
-    corners: Vec<Corner>,
+    /// Pixels changed since the corners were last updated. Corners within one pixel of these are
+    /// regenerated the next time they're needed.
+    dirty: Vec<TileRect>,
+    /// Corners bucketed by location, in squares of `CORNER_BUCKET_SIZE` pixels.
+    corners: TileGrid<Vec<Corner>>,
 }
 
 impl Index<TileIndex> for LightGrid {
@@ -36,40 +40,115 @@ impl Index<TileIndex> for LightGrid {
 
 impl IndexMut<TileIndex> for LightGrid {
     fn index_mut(&mut self, index: TileIndex) -> &mut Self::Output {
-        self.updated = true;
+        self.mark_dirty(TileRect {
+            origin: index,
+            size: vector![1, 1],
+        });
         &mut self.grid[index]
     }
 }
 
 impl LightGrid {
     pub const MAXIMUM_RAY_RANGE: f64 = 2048.0;
+    pub const CORNER_BUCKET_SIZE: isize = 32;
+    /// Changes closer than this many pixels to an existing dirty region are merged into it.
+    const DIRTY_MERGE_DISTANCE: usize = 16;
+    /// Past this many separate dirty regions, they're merged into one.
+    const MAXIMUM_DIRTY_REGIONS: usize = 16;
+
+    pub fn from_pixels(grid: TileGrid<Pixel>) -> Self {
+        Self {
+            dirty: vec![grid.bounds()],
+            grid,
+            corners: TileGrid::default(),
+        }
+    }
+
+    pub fn pixels(&self) -> &TileGrid<Pixel> {
+        &self.grid
+    }
 
     pub fn fill_tile(&mut self, index: TileIndex, pixel: Pixel) {
         let corner = index * super::TILE_SIZE;
 
+        self.mark_dirty(TileRect {
+            origin: corner,
+            size: vector![super::TILE_SIZE as usize, super::TILE_SIZE as usize],
+        });
+
         for x in corner.x..corner.x + super::TILE_SIZE {
             for y in corner.y..corner.y + super::TILE_SIZE {
-                self[point![x, y]] = pixel;
+                self.grid[point![x, y]] = pixel;
             }
         }
     }
 
-    pub fn corners(&mut self) -> &[Corner] {
-        if self.updated {
-            self.updated = false;
-            self.regenerate_corners();
+    fn mark_dirty(&mut self, bounds: TileRect) {
+        let merge_distance = Self::DIRTY_MERGE_DISTANCE as isize;
+
+        for dirty in &mut self.dirty {
+            let nearby = TileRect {
+                origin: dirty.origin - vector![merge_distance, merge_distance],
+                size: dirty.size.add_scalar(Self::DIRTY_MERGE_DISTANCE * 2),
+            };
+
+            if nearby.intersects(&bounds) {
+                dirty.expand_to_include_bounds(bounds, vector![0, 0]);
+                return;
+            }
         }
 
-        &self.corners
+        if self.dirty.len() < Self::MAXIMUM_DIRTY_REGIONS {
+            self.dirty.push(bounds);
+        } else {
+            let mut merged = bounds;
+
+            for dirty in self.dirty.drain(..) {
+                merged.expand_to_include_bounds(dirty, vector![0, 0]);
+            }
+
+            self.dirty.push(merged);
+        }
+    }
+
+    pub fn update_corners(&mut self) {
+        for dirty in mem::take(&mut self.dirty) {
+            // A corner depends on the pixels up and to the left of it, so each changed pixel
+            // affects the corners at all four of its own corners.
+            self.regenerate_corners(TileRect {
+                origin: dirty.origin,
+                size: dirty.size.add_scalar(1),
+            });
+        }
+    }
+
+    pub fn corners(&mut self) -> impl Iterator<Item = &Corner> {
+        self.update_corners();
+
+        self.corners.iter().flat_map(|(_, bucket)| bucket)
     }
 
-    fn regenerate_corners(&mut self) {
-        self.corners.clear();
+    /// Recomputes every corner with a location inside `region`.
+    fn regenerate_corners(&mut self, region: TileRect) {
+        if region.is_empty() {
+            return;
+        }
+
+        let buckets = Self::corner_buckets_of(region);
+
+        for x in buckets.left()..buckets.right() + 1 {
+            for y in buckets.top()..buckets.bottom() + 1 {
+                if self.corners[point![x, y]].is_empty() {
+                    continue;
+                }
 
-        let bounds = self.grid.bounds();
+                self.corners[point![x, y]]
+                    .retain(|corner| !region.contains_point(corner.location.map(|x| x as isize)));
+            }
+        }
 
-        for x in bounds.left()..bounds.right() + 2 {
-            for y in bounds.top()..bounds.bottom() + 2 {
+        for x in region.left()..region.right() + 1 {
+            for y in region.top()..region.bottom() + 1 {
                 let neighborhood = array::from_fn(|v| {
                     array::from_fn(|u| {
                         self.grid[point![x + u as isize - 1, y + v as isize - 1]].blocks_light()
@@ -77,8 +156,10 @@ impl LightGrid {
                 });
 
                 for &direction in CornerDirection::from_neighborhood(neighborhood) {
-                    self.corners.push(Corner {
-                        location: point![x as f64, y as f64],
+                    let location = point![x, y];
+
+                    self.corners[Self::corner_bucket_of(location)].push(Corner {
+                        location: location.map(|x| x as f64),
                         direction,
                     })
                 }
@@ -86,6 +167,53 @@ impl LightGrid {
         }
     }
 
+    fn corner_bucket_of(location: TileIndex) -> TileIndex {
+        location.map(|x| x.div_euclid(Self::CORNER_BUCKET_SIZE))
+    }
+
+    fn corner_buckets_of(region: TileRect) -> TileRect {
+        let min = Self::corner_bucket_of(region.min_corner());
+        let max = Self::corner_bucket_of(point![region.right(), region.bottom()]);
+
+        TileRect {
+            origin: min,
+            size: (max - min).map(|x| x as usize + 1),
+        }
+    }
+
+    /// The corners that a ray from `origin` could reach, skipping whole buckets that are out of
+    /// range or outside of `angle_range`. Corners must be up to date.
+    fn corners_visible_from(
+        &self,
+        origin: Point2<f64>,
+        angle_range: Option<AngleRange>,
+    ) -> impl Iterator<Item = &Corner> {
+        // Rays that stop just short of their maximum range still count as reaching corners a
+        // little further away, so leave some slack.
+        let range = Self::MAXIMUM_RAY_RANGE + 1.0;
+        let bucket_size = Self::CORNER_BUCKET_SIZE as f64;
+
+        let reachable_min = origin.map(|x| ((x - range) / bucket_size).floor() as isize);
+        let reachable_max = origin.map(|x| ((x + range) / bucket_size).floor() as isize);
+        let bounds = self.corners.bounds();
+
+        (bounds.left().max(reachable_min.x)..bounds.right().min(reachable_max.x) + 1)
+            .flat_map(move |x| {
+                (bounds.top().max(reachable_min.y)..bounds.bottom().min(reachable_max.y) + 1)
+                    .map(move |y| point![x, y])
+            })
+            .filter(move |&bucket| {
+                let min = bucket.map(|x| (x * Self::CORNER_BUCKET_SIZE) as f64) - origin;
+                let max = min.add_scalar(bucket_size - 1.0);
+                let nearest = Vector2::from_fn(|i, _| 0.0_f64.clamp(min[i], max[i]));
+
+                nearest.magnitude_squared() <= range * range
+                    && angle_range.is_none_or(|angle_range| angle_range.may_overlap_box(min, max))
+            })
+            .flat_map(|bucket| &self.corners[bucket])
+            .filter(move |corner| (corner.location - origin).magnitude_squared() <= range * range)
+    }
+
     pub fn draw(&mut self, solid_color: Color, none_color: Color) {
         if self.grid.bounds().area() == 0 {
             return;
@@ -178,13 +306,12 @@ impl LightGrid {
             origin,
             rays: Vec::new(),
             range: angle_range,
+            radius: None,
         };
 
         let mut unorganized_rays = Vec::new();
 
-        // HACK: Update the corners, then get them without rust thinking we still need a unique
-        // pointer to self.
-        let _ = self.corners();
+        self.update_corners();
 
         let collision_function = |_, index| self[index].blocks_light();
 
@@ -217,8 +344,6 @@ impl LightGrid {
         } else {
             // HACK: The lighting system breaks when one of these rays hits a corner, so use an
             // irrational number for their direction.
-            use std::f64::consts::PI;
-
             for direction in [
                 UnitVector2::new_normalize(vector![1.0, PI]),
                 UnitVector2::new_normalize(vector![-PI, 1.0]),
@@ -240,7 +365,7 @@ impl LightGrid {
             }
         }
 
-        for corner in &self.corners {
+        for corner in self.corners_visible_from(area.origin, area.range) {
             let offset_to_corner = corner.location - area.origin;
 
             if !(corner.direction.contains_offset(-offset_to_corner)
@@ -346,6 +471,15 @@ impl LightGrid {
                 continue;
             }
 
+            // When corners line up, several rays can end at the same far point. Only the one that
+            // continued past the nearest corner is on the right side of it.
+            let longest = chunk
+                .iter()
+                .filter(|ray| (ray.magnitude - longest.magnitude).abs() <= 1e-6)
+                .max_by_key(|ray| (ray.partition as i8 - shortest.partition as i8).abs())
+                .copied()
+                .unwrap_or(longest);
+
             let (left, right) = match shortest.partition.cmp(&longest.partition) {
                 Ordering::Less => (longest, shortest),
                 Ordering::Equal => (shortest, longest),
@@ -676,6 +810,7 @@ pub enum Pixel {
     #[default]
     Solid,
     Transparent,
+    Lethal,
 }
 
 impl Empty for Pixel {
@@ -712,6 +847,20 @@ impl Pixel {
     pub fn blocks_motion(&self) -> bool {
         matches!(self, Self::Solid | Self::Transparent)
     }
+
+    /// Returns `true` if a crate can't be pushed onto this pixel, since it would cover it up.
+    #[must_use]
+    pub fn blocks_crates(&self) -> bool {
+        matches!(self, Self::Solid | Self::Transparent | Self::Lethal)
+    }
+
+    /// Returns `true` if the material kind is [`Lethal`].
+    ///
+    /// [`Lethal`]: Pixel::Lethal
+    #[must_use]
+    pub fn is_lethal(&self) -> bool {
+        matches!(self, Self::Lethal)
+    }
 }
 
 #[derive(Clone, Default, Debug)]
@@ -719,19 +868,77 @@ pub struct LightArea {
     pub origin: Point2<f64>,
     pub rays: Vec<StoredRay>,
     pub range: Option<AngleRange>,
+    /// How far from the origin the area reaches, if it doesn't stop only at walls.
+    pub radius: Option<f64>,
 }
 
 impl LightArea {
     pub const PENETRATION: f32 = 4.0;
+    /// How much of the radius, measured inwards from the edge, fades out when drawn.
+    pub const FADE_FRACTION: f64 = 0.25;
+    /// The largest angle between consecutive points where the area is cut off by its radius.
+    pub const ARC_STEP: f64 = PI / 32.0;
+
+    pub fn with_radius(mut self, radius: Option<f64>) -> Self {
+        self.radius = radius;
+        self
+    }
 
+    /// The outline of the area, clipped to its radius if it has one.
     pub fn points(&self) -> impl Iterator<Item = Point2<f64>> {
-        self.rays
+        let points = self
+            .rays
             .iter()
             .map(|ray| self.origin + ray.offset)
-            .chain(self.range.is_some().then(|| self.origin))
+            .chain(self.range.is_some().then(|| self.origin));
+
+        match self.radius {
+            Some(radius) => clip_to_circle(&points.collect::<Vec<_>>(), self.origin, radius),
+            None => points.collect(),
+        }
+        .into_iter()
+    }
+
+    /// Whether `point` is within the angle range and radius, ignoring walls.
+    pub fn reaches(&self, point: Point2<f64>) -> bool {
+        let offset = point - self.origin;
+
+        self.range.is_none_or(|range| range.contains_offset(offset)) && self.is_within_radius(point)
+    }
+
+    pub fn is_within_radius(&self, point: Point2<f64>) -> bool {
+        self.radius
+            .is_none_or(|radius| (point - self.origin).magnitude_squared() <= radius.powi(2))
+    }
+
+    /// Whether `point` is within this area, which is the case when it's reached by a straight
+    /// path from the origin.
+    pub fn sees_point(&self, light_grid: &LightGrid, point: Point2<f64>) -> bool {
+        self.reaches(point) && light_grid.contains_path(self.origin, point)
+    }
+
+    /// How far `color` has faded to darkness at `distance` from the origin.
+    pub fn faded(&self, color: Color, distance: f64) -> Color {
+        let Some(radius) = self.radius else {
+            return color;
+        };
+
+        let fade_start = radius * (1.0 - Self::FADE_FRACTION);
+        let fade = ((distance - fade_start) / (radius - fade_start)).clamp(0.0, 1.0) as f32;
+
+        Color::new(
+            color.r * (1.0 - fade),
+            color.g * (1.0 - fade),
+            color.b * (1.0 - fade),
+            color.a + (1.0 - color.a) * fade,
+        )
     }
 
     pub fn mesh(&self, color: Color) -> Option<Mesh> {
+        if self.radius.is_some() {
+            return self.faded_mesh(color, false);
+        }
+
         let color = color.into();
 
         let vertices = self
@@ -762,6 +969,72 @@ impl LightArea {
         }
     }
 
+    /// A fan around the origin, which the area is always star shaped around, with a ring of
+    /// vertices where the fade starts so that only the edge fades. With `core_only`, the faded
+    /// ring is left out.
+    fn faded_mesh(&self, color: Color, core_only: bool) -> Option<Mesh> {
+        let radius = self.radius?;
+        let fade_start = radius * (1.0 - Self::FADE_FRACTION);
+
+        let outline = self
+            .points()
+            .filter(|point| (point - self.origin).magnitude_squared() > 1e-12)
+            .collect::<Vec<_>>();
+
+        if outline.len() < 2 {
+            return None;
+        }
+
+        let vertex = |point: Point2<f64>, color: Color| Vertex {
+            position: Vec3::new(point.x as f32, point.y as f32, 0.0),
+            uv: Vec2::ZERO,
+            color: color.into(),
+            normal: Vec4::ZERO,
+        };
+
+        let mut vertices = vec![vertex(self.origin, color)];
+        let mut indices = Vec::new();
+
+        for &point in &outline {
+            let offset = point - self.origin;
+            let distance = offset.magnitude();
+
+            vertices.push(vertex(
+                self.origin + offset * (fade_start.min(distance) / distance),
+                color,
+            ));
+            vertices.push(vertex(point, self.faded(color, distance)));
+        }
+
+        // A cone's outline starts and ends at the origin, so it doesn't wrap around.
+        let edges = if self.range.is_some() {
+            outline.len() - 1
+        } else {
+            outline.len()
+        };
+
+        for i in 0..edges {
+            let [inner_1, outer_1] = [1 + 2 * i, 2 + 2 * i].map(|x| x as u16);
+            let [inner_2, outer_2] = [
+                1 + 2 * ((i + 1) % outline.len()),
+                2 + 2 * ((i + 1) % outline.len()),
+            ]
+            .map(|x| x as u16);
+
+            indices.extend_from_slice(&[0, inner_1, inner_2]);
+
+            if !core_only {
+                indices.extend_from_slice(&[inner_1, outer_1, outer_2, inner_1, outer_2, inner_2]);
+            }
+        }
+
+        Some(Mesh {
+            vertices,
+            indices,
+            texture: None,
+        })
+    }
+
     pub fn draw_all(&self, direct_color: Color, wall_color: Color) {
         self.draw_direct_lighting(direct_color);
         self.draw_wall_lighting(wall_color);
@@ -773,6 +1046,14 @@ impl LightArea {
         }
     }
 
+    /// Redraws the part of the area that isn't faded, so that the faded edges of areas drawn
+    /// later don't darken it.
+    pub fn draw_unfaded_lighting(&self, color: Color) {
+        if let Some(mesh) = self.faded_mesh(color, true) {
+            models::draw_mesh(&mesh);
+        }
+    }
+
     pub fn draw_wall_lighting(&self, color: Color) {
         for window in self.rays.windows(2) {
             let &[
@@ -789,6 +1070,24 @@ impl LightArea {
                 continue;
             };
 
+            let (left_offset, right_offset) = match self.radius {
+                Some(radius) => {
+                    let Some((start, end)) =
+                        segment_within_circle(left_offset, right_offset, radius)
+                    else {
+                        continue;
+                    };
+                    let direction = right_offset - left_offset;
+
+                    (
+                        left_offset + direction * start,
+                        left_offset + direction * end,
+                    )
+                }
+                None => (left_offset, right_offset),
+            };
+            let color = self.faded(color, ((left_offset + right_offset) / 2.0).magnitude());
+
             let left_out = left_collision.out::<i8>();
             let right_out = right_collision.out::<i8>();
 
@@ -825,7 +1124,8 @@ impl LightArea {
 
         for &StoredRay { offset, collision } in &self.rays {
             if let Some(RayCollisionNormal::Corner(direction, true)) = collision {
-                if direction.is_concave() {
+                if direction.is_concave() && self.is_within_radius(self.origin + offset) {
+                    let color = self.faded(color, offset.magnitude());
                     let position = (self.origin + offset).map(|x| x as f32);
                     shapes::draw_rectangle(
                         position.x - Self::PENETRATION * direction.is_east() as i8 as f32,
@@ -840,12 +1140,124 @@ impl LightArea {
     }
 
     pub fn edge_intersects_line(&self, line: [Point2<f64>; 2]) -> bool {
-        self.points()
-            .zip(self.points().skip(1).chain(self.points().next()))
-            .any(|edge| lines_intersect(edge.into(), line))
+        let points = self.points().collect::<Vec<_>>();
+
+        points
+            .iter()
+            .zip(points.iter().skip(1).chain(points.first()))
+            .any(|(&start, &end)| lines_intersect([start, end], line))
     }
 }
 
+/// The range of `t` in `0.0..=1.0` for which `start + (end - start) * t` is within `radius` of
+/// the origin.
+fn segment_within_circle(
+    start: Vector2<f64>,
+    end: Vector2<f64>,
+    radius: f64,
+) -> Option<(f64, f64)> {
+    let direction = end - start;
+
+    let a = direction.magnitude_squared();
+    let b = 2.0 * start.dot(&direction);
+    let c = start.magnitude_squared() - radius.powi(2);
+
+    if a <= 1e-12 {
+        return (c <= 0.0).then_some((0.0, 1.0));
+    }
+
+    let discriminant = b.powi(2) - 4.0 * a * c;
+
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let enter = ((-b - discriminant.sqrt()) / (2.0 * a)).max(0.0);
+    let exit = ((-b + discriminant.sqrt()) / (2.0 * a)).min(1.0);
+
+    (enter <= exit).then_some((enter, exit))
+}
+
+/// Clips a polygon that's star shaped around `center` to the circle of `radius` around it,
+/// following the circle with points at most `LightArea::ARC_STEP` apart where the polygon leaves
+/// it.
+fn clip_to_circle(points: &[Point2<f64>], center: Point2<f64>, radius: f64) -> Vec<Point2<f64>> {
+    let winding = points
+        .iter()
+        .zip(points.iter().skip(1).chain(points.first()))
+        .map(|(start, end)| (start - center).perp(&(end - center)))
+        .sum::<f64>()
+        .signum();
+
+    let arc = |clipped: &mut Vec<Point2<f64>>, from: Vector2<f64>, to: Vector2<f64>| {
+        let start = from.y.atan2(from.x);
+        let mut angle = (to.y.atan2(to.x) - start) * winding;
+        angle = angle.rem_euclid(TAU);
+
+        // Leaving and coming back at the same place shouldn't go all the way around.
+        if TAU - angle <= 1e-6 {
+            return;
+        }
+
+        let steps = (angle / LightArea::ARC_STEP).ceil() as usize;
+
+        for step in 1..steps {
+            let angle = start + winding * angle * step as f64 / steps as f64;
+            clipped.push(center + vector![angle.cos(), angle.sin()] * radius);
+        }
+    };
+
+    let mut clipped = Vec::new();
+    let mut first_entry = None;
+    let mut exit = None;
+
+    for (&start, &end) in points
+        .iter()
+        .zip(points.iter().skip(1).chain(points.first()))
+    {
+        let start = start - center;
+        let end = end - center;
+
+        let Some((enter_time, exit_time)) = segment_within_circle(start, end, radius) else {
+            continue;
+        };
+
+        let entered = start + (end - start) * enter_time;
+
+        if enter_time > 0.0 {
+            match exit.take() {
+                Some(exit) => arc(&mut clipped, exit, entered),
+                None => first_entry = Some(entered),
+            }
+        }
+
+        clipped.push(center + entered);
+
+        if exit_time < 1.0 {
+            let left = start + (end - start) * exit_time;
+
+            clipped.push(center + left);
+            exit = Some(left);
+        }
+    }
+
+    match (exit, first_entry) {
+        (Some(exit), Some(first_entry)) => arc(&mut clipped, exit, first_entry),
+        // The polygon surrounds the whole circle.
+        (None, None) if clipped.is_empty() => {
+            let start = vector![radius, 0.0];
+
+            clipped.push(center + start);
+            arc(&mut clipped, start, -start);
+            clipped.push(center - start);
+            arc(&mut clipped, -start, start);
+        }
+        _ => (),
+    }
+
+    clipped
+}
+
 /// CREDIT: <https://www.geeksforgeeks.org/dsa/check-if-two-given-line-segments-intersect/>
 fn lines_intersect(lhs: [Point2<f64>; 2], rhs: [Point2<f64>; 2]) -> bool {
     #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -965,6 +1377,52 @@ impl AngleRange {
             None => false,
         }
     }
+
+    /// Returns `false` only if no offset between `min` and `max` is within this range.
+    pub fn may_overlap_box(&self, min: Vector2<f64>, max: Vector2<f64>) -> bool {
+        let contains_origin = (0..2).all(|i| min[i] <= 0.0 && 0.0 <= max[i]);
+        let contains_corner = [
+            vector![min.x, min.y],
+            vector![max.x, min.y],
+            vector![min.x, max.y],
+            vector![max.x, max.y],
+        ]
+        .into_iter()
+        .any(|corner| self.contains_offset(corner));
+
+        // If the box overlaps the range without containing the origin or any of its own corners,
+        // one of the edges of the range must pass through it.
+        contains_origin
+            || contains_corner
+            || ray_intersects_box(self.left, min, max)
+            || ray_intersects_box(self.right, min, max)
+    }
+}
+
+/// Whether a ray from the origin passes through the box between `min` and `max`.
+fn ray_intersects_box(direction: UnitVector2<f64>, min: Vector2<f64>, max: Vector2<f64>) -> bool {
+    const EPSILON: f64 = 1e-6;
+
+    let mut enter = 0.0_f64;
+    let mut exit = f64::INFINITY;
+
+    for i in 0..2 {
+        if direction[i].abs() <= EPSILON {
+            if min[i] > EPSILON || max[i] < -EPSILON {
+                return false;
+            }
+
+            continue;
+        }
+
+        let time_1 = (min[i] - EPSILON) / direction[i];
+        let time_2 = (max[i] + EPSILON) / direction[i];
+
+        enter = enter.max(time_1.min(time_2));
+        exit = exit.min(time_1.max(time_2));
+    }
+
+    enter <= exit
 }
 
 /// CREDIT: <https://theshoemaker.de/posts/ray-casting-in-2d-grids>
@@ -1175,3 +1633,727 @@ fn index_of_location(location: Point2<f64>, direction: Vector2<f64>) -> Point2<i
     })
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::*;
+    use crate::level::{
+        Level,
+        entity_tracker::entity::{Entity, pushable_crate::Crate},
+    };
+
+    /// How far a point has to be from a wall, or into one, to be certain whether it's seen. Anything
+    /// closer could reasonably go either way, such as rays grazing a corner.
+    const MARGIN: f64 = 0.05;
+    /// How far an offset has to be from the edge of an angle range, in radians.
+    const ANGLE_MARGIN: f64 = 1e-3;
+
+    /// A xorshift generator, so every run tests the same grids.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        fn float(&mut self) -> f64 {
+            (self.next() >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        fn direction(&mut self) -> UnitVector2<f64> {
+            UnitComplex::new(self.float() * TAU) * UnitVector2::new_normalize(vector![1.0, 0.0])
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum Visibility {
+        Visible,
+        Hidden,
+        Unclear,
+    }
+
+    /// An open room of `size` pixels with scattered pixels, blocks and diagonal walls in it. Outside
+    /// of it is solid.
+    fn random_grid(rng: &mut Rng, size: isize) -> LightGrid {
+        let mut grid = LightGrid::default();
+
+        for x in 0..size {
+            for y in 0..size {
+                grid[point![x, y]] = Pixel::None;
+            }
+        }
+
+        for _ in 0..size * size / 16 {
+            let index = point![rng.below(size as u64), rng.below(size as u64)].map(|x| x as isize);
+            grid[index] = Pixel::Solid;
+        }
+
+        for _ in 0..3 {
+            let corner = point![rng.below(size as u64), rng.below(size as u64)].map(|x| x as isize);
+            let extent = vector![rng.below(5) + 1, rng.below(5) + 1].map(|x| x as isize);
+
+            for x in corner.x..(corner.x + extent.x).min(size) {
+                for y in corner.y..(corner.y + extent.y).min(size) {
+                    grid[point![x, y]] = Pixel::Solid;
+                }
+            }
+        }
+
+        for _ in 0..2 {
+            let start = point![rng.below(size as u64), rng.below(size as u64)].map(|x| x as isize);
+            let step = vector![
+                if rng.below(2) == 0 { 1 } else { -1 },
+                if rng.below(2) == 0 { 1 } else { -1 }
+            ];
+
+            for i in 0..rng.below(8) as isize + 2 {
+                let index = start + step * i;
+
+                if (0..size).contains(&index.x) && (0..size).contains(&index.y) {
+                    grid[index] = Pixel::Solid;
+                }
+            }
+        }
+
+        grid
+    }
+
+    fn is_solid(grid: &LightGrid, location: Point2<f64>) -> bool {
+        grid[location.map(|x| x.floor() as isize)].blocks_light()
+    }
+
+    /// Whether every pixel touching the square of half width `MARGIN` around `location` is solid
+    /// (`true`) or open (`false`).
+    fn square_is_all(grid: &LightGrid, location: Point2<f64>, solid: bool) -> bool {
+        [[-1.0, -1.0], [1.0, -1.0], [-1.0, 1.0], [1.0, 1.0]]
+            .into_iter()
+            .all(|offset| is_solid(grid, location + Vector2::from(offset) * MARGIN) == solid)
+    }
+
+    /// Walks from `origin` to `target` in steps small enough that no wall can be skipped over.
+    fn brute_force_visibility(
+        grid: &LightGrid,
+        origin: Point2<f64>,
+        target: Point2<f64>,
+    ) -> Visibility {
+        let offset = target - origin;
+        let steps = (offset.magnitude() / MARGIN).ceil().max(1.0) as usize;
+        let mut clear = true;
+
+        for i in 0..=steps {
+            let location = origin + offset * (i as f64 / steps as f64);
+
+            if square_is_all(grid, location, true) {
+                return Visibility::Hidden;
+            }
+
+            clear &= square_is_all(grid, location, false);
+        }
+
+        if clear {
+            Visibility::Visible
+        } else {
+            Visibility::Unclear
+        }
+    }
+
+    fn brute_force_range_visibility(range: Option<AngleRange>, offset: Vector2<f64>) -> Visibility {
+        let Some(range) = range else {
+            return Visibility::Visible;
+        };
+
+        let inside = [-ANGLE_MARGIN, ANGLE_MARGIN]
+            .map(|angle| range.contains_offset(UnitComplex::new(angle) * offset));
+
+        match inside {
+            [true, true] => Visibility::Visible,
+            [false, false] => Visibility::Hidden,
+            _ => Visibility::Unclear,
+        }
+    }
+
+    fn brute_force_radius_visibility(radius: Option<f64>, offset: Vector2<f64>) -> Visibility {
+        let Some(radius) = radius else {
+            return Visibility::Visible;
+        };
+
+        let distance = offset.magnitude();
+
+        if distance < radius - MARGIN {
+            Visibility::Visible
+        } else if distance > radius + MARGIN {
+            Visibility::Hidden
+        } else {
+            Visibility::Unclear
+        }
+    }
+
+    fn combine(lhs: Visibility, rhs: Visibility) -> Visibility {
+        match (lhs, rhs) {
+            (Visibility::Hidden, _) | (_, Visibility::Hidden) => Visibility::Hidden,
+            (Visibility::Visible, Visibility::Visible) => Visibility::Visible,
+            _ => Visibility::Unclear,
+        }
+    }
+
+    /// Even-odd rule, so it's only meaningful away from the edges.
+    fn polygon_contains(points: &[Point2<f64>], location: Point2<f64>) -> bool {
+        let mut inside = false;
+
+        for i in 0..points.len() {
+            let a = points[i];
+            let b = points[(i + 1) % points.len()];
+
+            if (a.y > location.y) != (b.y > location.y)
+                && location.x < a.x + (location.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            {
+                inside = !inside;
+            }
+        }
+
+        inside
+    }
+
+    /// Checks that every clearly visible target is inside the traced area, and every clearly hidden
+    /// one is outside of it. Returns how many targets were clear either way.
+    fn check_light_area(
+        grid: &mut LightGrid,
+        origin: Point2<f64>,
+        range: Option<AngleRange>,
+        radius: Option<f64>,
+        targets: impl IntoIterator<Item = Point2<f64>>,
+    ) -> usize {
+        let area = grid.trace_light_from(origin, range).with_radius(radius);
+        let points = area.points().collect::<Vec<_>>();
+        let mut checked = 0;
+
+        for target in targets {
+            if is_solid(grid, target) || (target - origin).magnitude() < MARGIN {
+                continue;
+            }
+
+            let visibility = combine(
+                combine(
+                    brute_force_visibility(grid, origin, target),
+                    brute_force_range_visibility(range, target - origin),
+                ),
+                brute_force_radius_visibility(radius, target - origin),
+            );
+
+            let expected = match visibility {
+                Visibility::Visible => true,
+                Visibility::Hidden => false,
+                Visibility::Unclear => continue,
+            };
+
+            assert_eq!(
+                polygon_contains(&points, target),
+                expected,
+                "{target} seen from {origin} with {range:?} and {radius:?}, traced as {points:?}",
+            );
+            checked += 1;
+        }
+
+        checked
+    }
+
+    fn random_origin(rng: &mut Rng, grid: &LightGrid, size: isize) -> Point2<f64> {
+        loop {
+            let mut origin = point![rng.float(), rng.float()] * size as f64;
+
+            // Sometimes line up with the pixel grid, so rays run along walls and through corners.
+            match rng.below(4) {
+                0 => origin = origin.map(f64::round),
+                1 => origin.x = origin.x.round(),
+                2 => origin.y = origin.y.round() + 1e-7,
+                _ => (),
+            }
+
+            if brute_force_visibility(grid, origin, origin) == Visibility::Visible {
+                return origin;
+            }
+        }
+    }
+
+    fn pixel_centers(size: isize) -> impl Iterator<Item = Point2<f64>> {
+        (0..size).flat_map(move |x| (0..size).map(move |y| point![x as f64 + 0.5, y as f64 + 0.5]))
+    }
+
+    /// How many grids or origins a randomized test checks, multiplied by `LIGHT_GRID_TEST_SCALE`.
+    /// The defaults keep `cargo test` quick, so raise it after changing how light is traced.
+    fn scaled(count: usize) -> usize {
+        let scale = std::env::var("LIGHT_GRID_TEST_SCALE")
+            .ok()
+            .and_then(|scale| scale.parse().ok())
+            .unwrap_or(1);
+
+        count * scale
+    }
+
+    #[test]
+    fn corners_match_neighborhoods() {
+        for bits in 0..16u8 {
+            let neighborhood: [[bool; 2]; 2] =
+                array::from_fn(|v| array::from_fn(|u| bits & 1 << (v * 2 + u) != 0));
+            let solid_count = bits.count_ones();
+
+            // The pixel each corner direction points out into.
+            let pixel_out = |direction: CornerDirection| {
+                let out = direction.out::<i8>();
+                neighborhood[(out.y > 0) as usize][(out.x > 0) as usize]
+            };
+            let pixel_in = |direction: CornerDirection| {
+                let out = direction.out::<i8>();
+                neighborhood[(out.y < 0) as usize][(out.x < 0) as usize]
+            };
+
+            let corners = CornerDirection::from_neighborhood(neighborhood);
+            let diagonal = neighborhood[0][0] == neighborhood[1][1]
+                && neighborhood[0][1] == neighborhood[1][0]
+                && neighborhood[0][0] != neighborhood[0][1];
+
+            let expected_count = match solid_count {
+                1 | 3 => 1,
+                2 if diagonal => 2,
+                _ => 0,
+            };
+
+            assert_eq!(corners.len(), expected_count, "{neighborhood:?}");
+
+            for &corner in corners {
+                assert!(!pixel_out(corner), "{corner:?} in {neighborhood:?}");
+
+                if corner.is_convex() {
+                    assert_eq!(solid_count, 1, "{corner:?} in {neighborhood:?}");
+                    assert!(pixel_in(corner), "{corner:?} in {neighborhood:?}");
+                } else {
+                    assert!(solid_count >= 2, "{corner:?} in {neighborhood:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn raycast_stops_at_first_wall() {
+        let grids = scaled(5);
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..grids {
+            let grid = random_grid(&mut rng, 24);
+
+            for _ in 0..50 {
+                let start = point![rng.float(), rng.float()] * 24.0;
+
+                if is_solid(&grid, start) {
+                    continue;
+                }
+
+                let direction = rng.direction();
+                let (finish, _) = raycast(
+                    |_, index| grid[index].blocks_light(),
+                    start,
+                    direction,
+                    LightGrid::MAXIMUM_RAY_RANGE,
+                );
+
+                // March in small steps until inside a wall.
+                let step = 1e-3;
+                let mut distance = 0.0;
+
+                while !is_solid(&grid, start + direction.into_inner() * (distance + step)) {
+                    distance += step;
+                }
+
+                let near_lattice_point = finish.iter().all(|x| (x - x.round()).abs() < 1e-2);
+
+                if near_lattice_point {
+                    continue;
+                }
+
+                assert!(
+                    ((finish - start).magnitude() - distance).abs() <= step * 2.0,
+                    "from {start} towards {direction:?}: {finish} vs {}",
+                    start + direction.into_inner() * distance,
+                );
+                assert!(
+                    finish.iter().any(|x| (x - x.round()).abs() <= 1e-6),
+                    "{finish} should be on a pixel edge",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rays_are_ordered_counter_clockwise() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        // The angle from `reference` to `offset` in the direction `compare_ray_angles` sorts by.
+        let angle = |reference: UnitVector2<f64>, offset: Vector2<f64>| {
+            reference
+                .perp(&offset)
+                .atan2(reference.dot(&offset))
+                .rem_euclid(TAU)
+        };
+
+        for _ in 0..10_000 {
+            let reference = rng.direction();
+            let [lhs, rhs] = [(); 2].map(|_| {
+                Ray::new(
+                    rng.direction().into_inner() * (rng.float() * 10.0 + 0.1),
+                    RayPartition::None,
+                    None,
+                )
+            });
+
+            let [lhs_angle, rhs_angle] = [lhs, rhs].map(|ray| angle(reference, ray.offset));
+
+            if [lhs_angle, rhs_angle]
+                .iter()
+                .any(|angle| !(1e-3..=TAU - 1e-3).contains(angle))
+                || (lhs_angle - rhs_angle).abs() < 1e-3
+            {
+                continue;
+            }
+
+            assert_eq!(
+                compare_ray_angles(lhs, rhs, reference, 0.0),
+                lhs_angle.total_cmp(&rhs_angle),
+                "{lhs:?} and {rhs:?} from {reference:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn traced_areas_match_brute_force() {
+        let grids = scaled(1);
+        let mut rng = Rng(0xdead_beef_cafe_f00d);
+        let size = 20;
+        let mut checked = 0;
+
+        for _ in 0..grids {
+            let mut grid = random_grid(&mut rng, size);
+
+            for _ in 0..4 {
+                let origin = random_origin(&mut rng, &grid, size);
+                let range = (rng.below(2) == 0).then(|| {
+                    AngleRange::from_direction_and_width(rng.direction(), rng.float() * 5.0 + 0.5)
+                });
+
+                let jittered = (0..200)
+                    .map(|_| point![rng.float(), rng.float()] * size as f64)
+                    .collect::<Vec<_>>();
+
+                checked += check_light_area(
+                    &mut grid,
+                    origin,
+                    range,
+                    None,
+                    pixel_centers(size).chain(jittered),
+                );
+            }
+        }
+
+        // Make sure the margins didn't make every case unclear.
+        assert!(checked > grids * 300, "only {checked} targets were checked");
+    }
+
+    #[test]
+    fn radius_clips_traced_areas() {
+        let grids = scaled(1);
+        let mut rng = Rng(0x0123_4567_89ab_cdef);
+        let size = 20;
+        let mut checked = 0;
+
+        for _ in 0..grids {
+            let mut grid = random_grid(&mut rng, size);
+
+            for _ in 0..4 {
+                let origin = random_origin(&mut rng, &grid, size);
+                let range = (rng.below(2) == 0).then(|| {
+                    AngleRange::from_direction_and_width(rng.direction(), rng.float() * 5.0 + 0.5)
+                });
+                // Sometimes large enough that no wall gets in the way of the circle.
+                let radius = 1.0 + rng.float() * size as f64;
+
+                let jittered = (0..400)
+                    .map(|_| point![rng.float(), rng.float()] * size as f64)
+                    .collect::<Vec<_>>();
+
+                checked += check_light_area(
+                    &mut grid,
+                    origin,
+                    range,
+                    Some(radius),
+                    pixel_centers(size).chain(jittered),
+                );
+            }
+        }
+
+        assert!(checked > grids * 250, "only {checked} targets were checked");
+    }
+
+    #[test]
+    fn radius_in_open_space() {
+        let mut grid = LightGrid::default();
+
+        for x in -64..64 {
+            for y in -64..64 {
+                grid[point![x, y]] = Pixel::None;
+            }
+        }
+
+        let origin = point![0.3, 0.6];
+        let area = grid.trace_light_from(origin, None).with_radius(Some(10.0));
+        let points = area.points().collect::<Vec<_>>();
+
+        assert!(
+            points
+                .iter()
+                .all(|point| ((point - origin).magnitude() - 10.0).abs() <= 1e-9),
+            "{points:?}",
+        );
+        assert!(polygon_contains(&points, origin + vector![9.9, 0.0]));
+        assert!(!polygon_contains(&points, origin + vector![10.1, 0.0]));
+        assert!(area.sees_point(&grid, origin + vector![0.0, -9.9]));
+        assert!(!area.sees_point(&grid, origin + vector![0.0, -10.1]));
+    }
+
+    #[test]
+    fn corner_grazing() {
+        let mut grid = LightGrid::default();
+
+        for x in 0..16 {
+            for y in 0..16 {
+                grid[point![x, y]] = Pixel::None;
+            }
+        }
+
+        // Two pixels touching at a corner, lined up with the origin along a diagonal, and one
+        // lined up along a row.
+        grid[point![6, 6]] = Pixel::Solid;
+        grid[point![7, 7]] = Pixel::Solid;
+        grid[point![10, 2]] = Pixel::Solid;
+
+        for origin in [
+            point![2.0, 2.0],
+            point![2.0 + 1e-7, 2.0 - 1e-7],
+            point![4.0, 2.0],
+            point![3.5, 3.5],
+        ] {
+            for range in [
+                None,
+                Some(AngleRange::from_direction_and_width(
+                    UnitVector2::new_normalize(vector![1.0, 1.0]),
+                    1.0,
+                )),
+            ] {
+                check_light_area(&mut grid, origin, range, None, pixel_centers(16));
+            }
+
+            // Just past the far corner of the diagonal pair, which blocks light through its
+            // middle.
+            let area = grid.trace_light_from(origin, None);
+            let points = area.points().collect::<Vec<_>>();
+
+            if (origin - point![2.0, 2.0]).magnitude() < 1e-6 {
+                assert!(!polygon_contains(&points, point![8.5, 8.5]));
+            }
+        }
+    }
+
+    #[test]
+    fn crates_are_seen_when_any_of_them_is() {
+        let grids = scaled(3);
+        let mut rng = Rng(0x1234_5678_9abc_def1);
+        let size = 32;
+        let mut checked = 0;
+
+        for _ in 0..grids {
+            let mut grid = random_grid(&mut rng, size);
+            let position = point![
+                rng.below(size as u64 - 8) as f64 + 4.0,
+                rng.below(size as u64 - 8) as f64 + 4.0
+            ];
+            let crate_entity = Crate {
+                position,
+                ..Default::default()
+            };
+
+            // Crates block light in game too, so they can only be seen from outside.
+            crate_entity.fill_light_grid(&mut grid, Pixel::Solid);
+
+            // Just outside of the crate, all the way around.
+            let half_size = 4.0 + MARGIN * 2.0;
+            let outline = (0..64)
+                .map(|i| {
+                    let t = i as f64 / 16.0;
+                    let offset = match i / 16 {
+                        0 => vector![t * 2.0 - 1.0, -1.0],
+                        1 => vector![1.0, t * 2.0 - 3.0],
+                        2 => vector![5.0 - t * 2.0, 1.0],
+                        _ => vector![-1.0, 7.0 - t * 2.0],
+                    };
+
+                    position + offset * half_size
+                })
+                .collect::<Vec<_>>();
+
+            for _ in 0..8 {
+                let origin = random_origin(&mut rng, &grid, size);
+
+                if (origin - position).abs().max() < half_size + 1.0 {
+                    continue;
+                }
+
+                let range = (rng.below(2) == 0).then(|| {
+                    AngleRange::from_direction_and_width(rng.direction(), rng.float() * 5.0 + 0.5)
+                });
+
+                let visibilities = outline.iter().map(|&target| {
+                    combine(
+                        brute_force_visibility(&grid, origin, target),
+                        brute_force_range_visibility(range, target - origin),
+                    )
+                });
+
+                let mut any_visible = false;
+                let mut all_hidden = true;
+
+                for visibility in visibilities {
+                    any_visible |= visibility == Visibility::Visible;
+                    all_hidden &= visibility == Visibility::Hidden;
+                }
+
+                let area = grid.trace_light_from(origin, range);
+                let seen = crate_entity.is_within_view_area(&grid, &area);
+
+                if any_visible {
+                    assert!(seen, "crate at {position} from {origin} with {range:?}");
+                    checked += 1;
+                } else if all_hidden {
+                    assert!(!seen, "crate at {position} from {origin} with {range:?}");
+                    checked += 1;
+                }
+            }
+        }
+
+        assert!(checked > grids * 2, "only {checked} cases were checked");
+    }
+
+    #[test]
+    fn corners_stay_up_to_date() {
+        let grids = scaled(3);
+        let mut rng = Rng(0x0bad_5eed_0bad_5eed);
+
+        let corner_set = |grid: &mut LightGrid| {
+            let mut corners = grid
+                .corners()
+                .map(|corner| {
+                    (
+                        corner.location.x as isize,
+                        corner.location.y as isize,
+                        corner.direction as u8,
+                    )
+                })
+                .collect::<Vec<_>>();
+            corners.sort_unstable();
+            corners
+        };
+
+        for _ in 0..grids {
+            let mut grid = random_grid(&mut rng, 40);
+
+            for _ in 0..10 {
+                let _ = grid.corners();
+
+                for _ in 0..rng.below(40) {
+                    let index = point![rng.below(50), rng.below(50)].map(|x| x as isize - 5);
+
+                    grid[index] = if rng.below(2) == 0 {
+                        Pixel::Solid
+                    } else {
+                        Pixel::None
+                    };
+                }
+
+                let mut rebuilt = LightGrid::from_pixels(grid.pixels().clone());
+
+                assert_eq!(corner_set(&mut grid), corner_set(&mut rebuilt));
+            }
+        }
+    }
+
+    #[test]
+    fn boxes_overlapping_angle_ranges() {
+        let mut rng = Rng(0x5851_f42d_4c95_7f2d);
+
+        for _ in 0..2000 {
+            let range =
+                AngleRange::from_direction_and_width(rng.direction(), rng.float() * 6.0 + 0.1);
+            let min = vector![rng.float(), rng.float()] * 40.0 - vector![20.0, 20.0];
+            let max = min + vector![rng.float(), rng.float()] * 10.0;
+
+            let sampled = (0..=20).any(|i| {
+                (0..=20).any(|j| {
+                    let offset =
+                        min + (max - min).component_mul(&vector![i as f64, j as f64]) / 20.0;
+                    range.contains_offset(offset)
+                })
+            });
+
+            if sampled {
+                assert!(range.may_overlap_box(min, max), "{range:?} {min} {max}");
+            }
+        }
+    }
+
+    #[test]
+    fn diagonals_1() {
+        let origins = scaled(1);
+        let mut level = Level::new("diagonals_1".to_owned());
+        level.reset().unwrap();
+
+        let grid = &mut level.light_grid;
+        let bounds = grid.pixels().bounds();
+        let mut rng = Rng(0x3c6e_f372_fe94_f82b);
+        let mut checked = 0;
+
+        for _ in 0..origins {
+            let origin = loop {
+                let origin = point![
+                    bounds.left() as f64 + rng.float() * bounds.size.x as f64,
+                    bounds.top() as f64 + rng.float() * bounds.size.y as f64
+                ];
+
+                if brute_force_visibility(grid, origin, origin) == Visibility::Visible {
+                    break origin;
+                }
+            };
+
+            let range = AngleRange::from_direction_and_width(rng.direction(), TAU / 4.0);
+
+            // Nearby pixels, since the brute force check is slow over long distances.
+            let targets = (-16..16)
+                .flat_map(|x| (-16..16).map(move |y| vector![x as f64, y as f64]))
+                .map(|offset| origin.map(f64::floor) + offset + vector![0.5, 0.5])
+                .collect::<Vec<_>>();
+
+            checked += check_light_area(grid, origin, None, None, targets.iter().copied());
+            checked += check_light_area(grid, origin, Some(range), None, targets);
+        }
+
+        assert!(
+            checked > origins * 125,
+            "only {checked} targets were checked"
+        );
+    }
+}
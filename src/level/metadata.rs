@@ -0,0 +1,183 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use macroquad::{color::colors, text};
+use nalgebra::point;
+use serde::{Deserialize, Serialize};
+use slotmap::SecondaryMap;
+
+use crate::level::{
+    EntityKey, Level, UPDATE_DT,
+    hud::{TEXT_LINE_HEIGHT, draw_text_line},
+};
+
+/// How many seconds the title card stays on screen.
+pub const TITLE_CARD_DURATION: f64 = 4.0;
+
+/// Optional information about a level, stored after its entities. Levels without any are saved
+/// exactly as before.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+pub struct LevelMetadata {
+    /// Shown instead of the file name when not empty.
+    pub title: String,
+    pub author: String,
+    /// A short summary for level lists and tools.
+    pub description: String,
+    /// How many loops the level can be finished in.
+    pub par_loops: Option<u32>,
+    /// In seconds.
+    pub par_time: Option<f64>,
+    /// Shown under the title when the level is entered.
+    pub intro_text: String,
+    /// Players' view radii, which aren't part of their own data so that levels from before they
+    /// existed still load. Only filled in while saving and loading, the players hold them
+    /// otherwise.
+    pub view_radii: SecondaryMap<EntityKey, f64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MetadataField {
+    Title,
+    Author,
+    Description,
+    ParLoops,
+    ParTime,
+    IntroText,
+}
+
+impl FromStr for MetadataField {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(MetadataField::Title),
+            "author" => Ok(MetadataField::Author),
+            "description" => Ok(MetadataField::Description),
+            "par_loops" => Ok(MetadataField::ParLoops),
+            "par_time" => Ok(MetadataField::ParTime),
+            "intro" => Ok(MetadataField::IntroText),
+            _ => Err(()),
+        }
+    }
+}
+
+impl LevelMetadata {
+    /// An empty value clears the field. Fails if a par value isn't a number.
+    pub fn set(&mut self, field: MetadataField, value: &str) -> Result<(), ()> {
+        fn parse_optional<T: FromStr>(value: &str) -> Result<Option<T>, ()> {
+            if value.is_empty() {
+                Ok(None)
+            } else {
+                value.parse().map(Some).map_err(|_| ())
+            }
+        }
+
+        match field {
+            MetadataField::Title => self.title = value.to_owned(),
+            MetadataField::Author => self.author = value.to_owned(),
+            MetadataField::Description => self.description = value.to_owned(),
+            MetadataField::ParLoops => self.par_loops = parse_optional(value)?,
+            MetadataField::ParTime => self.par_time = parse_optional(value)?,
+            MetadataField::IntroText => self.intro_text = value.to_owned(),
+        }
+
+        Ok(())
+    }
+
+    pub fn par_text(&self) -> Option<String> {
+        let time = self.par_time.map(|time| {
+            let seconds = time.max(0.0).round() as u64;
+
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        });
+
+        match (self.par_loops, time) {
+            (Some(loops), Some(time)) => Some(format!("Par: {loops} loops in {time}")),
+            (Some(loops), None) => Some(format!("Par: {loops} loops")),
+            (None, Some(time)) => Some(format!("Par: {time}")),
+            (None, None) => None,
+        }
+    }
+}
+
+impl Display for LevelMetadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "none".to_owned());
+
+        write!(
+            f,
+            "title: \"{}\", author: \"{}\", description: \"{}\", par_loops: {}, par_time: {}, \
+             intro: \"{}\"",
+            self.title,
+            self.author,
+            self.description,
+            optional(self.par_loops.map(|loops| loops.to_string())),
+            optional(self.par_time.map(|time| time.to_string())),
+            self.intro_text,
+        )
+    }
+}
+
+impl Level {
+    /// The metadata title, or the file name if there isn't one.
+    pub fn display_title(&self) -> &str {
+        if self.metadata.title.is_empty() {
+            &self.level_name
+        } else {
+            &self.metadata.title
+        }
+    }
+
+    /// Shows the title card, starts counting stats afresh and loads the ghost if a different level
+    /// than before was just loaded.
+    pub fn enter_level(&mut self) {
+        if self.entered_level.as_ref() != Some(&self.level_name) {
+            self.entered_level = Some(self.level_name.clone());
+            self.title_card = Some(0.0);
+            self.reset_stats();
+            self.load_ghost();
+        }
+    }
+
+    pub fn update_title_card(&mut self) {
+        if let Some(age) = &mut self.title_card {
+            *age += UPDATE_DT;
+
+            if *age > TITLE_CARD_DURATION {
+                self.title_card = None;
+            }
+        }
+    }
+
+    pub fn draw_title_card(&self) {
+        if self.title_card.is_none() {
+            return;
+        }
+
+        let mut lines = vec![(self.display_title().to_owned(), colors::WHITE)];
+
+        if !self.metadata.author.is_empty() {
+            lines.push((format!("by {}", self.metadata.author), colors::LIGHTGRAY));
+        }
+
+        if !self.metadata.intro_text.is_empty() {
+            lines.push((self.metadata.intro_text.clone(), colors::LIGHTGRAY));
+        }
+
+        if let Some(par) = self.metadata.par_text() {
+            lines.push((par, colors::GRAY));
+        }
+
+        let screen_rect = crate::screen_rect();
+        let mut y = screen_rect.y + screen_rect.h / 4.0;
+
+        for (line, color) in lines {
+            let width = text::measure_text(&line, None, 8, 1.0).width;
+
+            draw_text_line(&line, point![-width / 2.0, y], color);
+            y += TEXT_LINE_HEIGHT;
+        }
+    }
+}
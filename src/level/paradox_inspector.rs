@@ -0,0 +1,163 @@
+use std::sync::atomic::{self, AtomicBool};
+
+use macroquad::{
+    color::{Color, colors},
+    shapes,
+};
+use nalgebra::point;
+
+use crate::level::{
+    Level,
+    entity_tracker::entity::{
+        EntityVisibleState,
+        player::{ParadoxComparison, Player, PlayerState},
+    },
+    hud::{TEXT_LINE_HEIGHT, draw_text_line},
+};
+
+pub const RECORDED_COLOR: Color = Color::new(0.3, 0.6, 1.0, 1.0);
+pub const OBSERVED_COLOR: Color = Color::new(1.0, 0.8, 0.2, 1.0);
+pub const WORST_COLOR: Color = colors::RED;
+
+/// Whether the inspector is open in any level, so past selves know to keep a `ParadoxReport`.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+pub fn is_active() -> bool {
+    ACTIVE.load(atomic::Ordering::Relaxed)
+}
+
+impl Level {
+    pub fn toggle_paradox_inspector(&mut self) {
+        self.paradox_inspector_active ^= true;
+
+        ACTIVE.store(self.paradox_inspector_active, atomic::Ordering::Relaxed);
+    }
+
+    /// Shows what every past self compared on the last frame, and which comparison confused it
+    /// the most.
+    pub fn draw_paradox_inspector(&self) {
+        let screen_rect = crate::screen_rect();
+        let mut start = point![
+            screen_rect.x + 2.0,
+            screen_rect.y + 8.0 + TEXT_LINE_HEIGHT * 2.0
+        ];
+
+        for (key, entity) in &self.entities {
+            let Some(player) = entity.inner.as_player() else {
+                continue;
+            };
+
+            if player.state == PlayerState::Active || player.state == PlayerState::Future {
+                continue;
+            }
+
+            let header = if let Some(report) = &player.paradox_report {
+                format!(
+                    "{key:?} {:?} frame {} confusion {:.2} paradox {}",
+                    player.state,
+                    report.frame,
+                    player.confusion,
+                    report
+                        .paradox_level()
+                        .map_or("none".to_string(), |(level, _)| format!("{level:.2}")),
+                )
+            } else {
+                format!(
+                    "{key:?} {:?} confusion {:.2} nothing compared",
+                    player.state, player.confusion,
+                )
+            };
+
+            draw_text_line(&header, start, colors::WHITE);
+            start.y += TEXT_LINE_HEIGHT;
+
+            let Some(report) = &player.paradox_report else {
+                continue;
+            };
+
+            let worst = report.worst().map(|worst| worst.key);
+
+            for comparison in &report.comparisons {
+                let color = if Some(comparison.key) == worst {
+                    WORST_COLOR
+                } else if comparison.error.is_some() {
+                    OBSERVED_COLOR
+                } else {
+                    colors::LIGHTGRAY
+                };
+
+                let name = self
+                    .entities
+                    .get(comparison.key)
+                    .map_or("removed", |entity| entity.inner.typetag_name());
+
+                let line = format!(
+                    "  {:?} {name}: recorded {} observed {} -> {}",
+                    comparison.key,
+                    describe_state(comparison.recorded),
+                    describe_state(comparison.observed),
+                    comparison
+                        .error
+                        .map_or("match".to_string(), |error| format!("{error:.2}")),
+                );
+
+                draw_text_line(&line, start, color);
+                start.y += TEXT_LINE_HEIGHT;
+
+                if comparison.error.is_some() {
+                    draw_comparison(player, comparison, Some(comparison.key) == worst);
+                }
+            }
+        }
+    }
+}
+
+pub fn describe_state(state: Option<EntityVisibleState>) -> String {
+    match state {
+        Some(state) => format!(
+            "({:.1}, {:.1}) #{}",
+            state.position.x, state.position.y, state.extra
+        ),
+        None => "unseen".to_string(),
+    }
+}
+
+/// Marks where an entity was recorded and where it was observed, joined by a line.
+fn draw_comparison(player: &Player, comparison: &ParadoxComparison, worst: bool) {
+    const MARKER_SIZE: f32 = 4.0;
+
+    let positions =
+        [comparison.recorded, comparison.observed].map(|state| state.map(|state| state.position));
+
+    for (position, color) in positions.into_iter().zip([RECORDED_COLOR, OBSERVED_COLOR]) {
+        if let Some(position) = position {
+            shapes::draw_rectangle_lines(
+                position.x - MARKER_SIZE / 2.0,
+                position.y - MARKER_SIZE / 2.0,
+                MARKER_SIZE,
+                MARKER_SIZE,
+                1.0,
+                color,
+            );
+        }
+    }
+
+    if let [Some(recorded), Some(observed)] = positions {
+        shapes::draw_line(
+            recorded.x,
+            recorded.y,
+            observed.x,
+            observed.y,
+            0.5,
+            colors::WHITE,
+        );
+    }
+
+    if worst {
+        let target = comparison.position().map(|x| x as f32);
+        let origin = player.position.map(|x| x as f32);
+
+        shapes::draw_line(origin.x, origin.y, target.x, target.y, 1.0, WORST_COLOR);
+        shapes::draw_circle_lines(target.x, target.y, MARKER_SIZE, 1.0, WORST_COLOR);
+    }
+}
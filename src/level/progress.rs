@@ -0,0 +1,170 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet, VecDeque},
+    io,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::level::{
+    Level,
+    entity_tracker::entity::GameAction,
+    filesystem::{self, FileSystem},
+    level_stats::LevelRecord,
+};
+
+pub const PROGRESS_FILE: &str = "progress";
+pub const FIRST_LEVEL: &str = "start";
+
+/// What the player has done across every play session.
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+pub struct Progress {
+    /// Levels that have been left through an exit elevator.
+    pub completed_levels: BTreeSet<String>,
+    /// The reached level that is the most exits away from `FIRST_LEVEL`.
+    pub furthest_level: Option<String>,
+    /// The level to resume from.
+    pub last_level: Option<String>,
+    /// The best stats for every level that has been completed.
+    pub level_records: BTreeMap<String, LevelRecord>,
+}
+
+impl Progress {
+    /// Falls back to no progress if the file is missing or can't be read.
+    pub fn load() -> Self {
+        let config = bincode::config::standard();
+
+        let Ok(data) = filesystem::load_save_file(PROGRESS_FILE) else {
+            return Self::default();
+        };
+
+        bincode::serde::decode_from_slice(&data, config)
+            .map(|(progress, _)| progress)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let config = bincode::config::standard();
+
+        let data = bincode::serde::encode_to_vec(self, config).unwrap();
+
+        filesystem::write_save_file(PROGRESS_FILE, &data)
+    }
+
+    /// A level can be played from the level select if it has been reached before.
+    pub fn is_unlocked(&self, graph: &LevelGraph, level: &str) -> bool {
+        level == FIRST_LEVEL
+            || self.completed_levels.contains(level)
+            || self.last_level.as_deref() == Some(level)
+            || self.furthest_level.as_deref() == Some(level)
+            || graph.levels.iter().any(|node| {
+                self.completed_levels.contains(&node.name)
+                    && node.exits.iter().any(|exit| exit == level)
+            })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LevelNode {
+    pub name: String,
+    /// From the level's metadata, and empty if it has none.
+    pub title: String,
+    /// How many exits away from `FIRST_LEVEL` this level is.
+    pub depth: usize,
+    pub exits: Vec<String>,
+}
+
+/// Every level reachable from `FIRST_LEVEL`, found by following exit elevators.
+#[derive(Clone, Default, Debug)]
+pub struct LevelGraph {
+    /// In order of increasing depth.
+    pub levels: Vec<LevelNode>,
+}
+
+impl LevelGraph {
+    /// Levels that fail to load are left out, along with anything only reachable through them.
+    pub fn new(filesystem: &FileSystem) -> Self {
+        let mut levels = Vec::new();
+        let mut found = HashSet::from([FIRST_LEVEL.to_owned()]);
+        let mut queue = VecDeque::from([(FIRST_LEVEL.to_owned(), 0)]);
+
+        while let Some((name, depth)) = queue.pop_front() {
+            let Ok(data) = filesystem.load(&name) else {
+                continue;
+            };
+
+            let Ok((_, entities, metadata)) = Level::decode_level(&data) else {
+                continue;
+            };
+
+            let mut exits = Vec::new();
+
+            for (_, entity) in &entities {
+                if let Some(elevator) = entity.inner.as_elevator()
+                    && let GameAction::LoadLevel(exit) = &elevator.action
+                    && !exits.contains(exit)
+                {
+                    exits.push(exit.clone());
+
+                    if found.insert(exit.clone()) {
+                        queue.push_back((exit.clone(), depth + 1));
+                    }
+                }
+            }
+
+            levels.push(LevelNode {
+                name,
+                title: metadata.title,
+                depth,
+                exits,
+            });
+        }
+
+        Self { levels }
+    }
+
+    pub fn depth(&self, level: &str) -> Option<usize> {
+        self.levels
+            .iter()
+            .find(|node| node.name == level)
+            .map(|node| node.depth)
+    }
+}
+
+impl Level {
+    pub fn level_graph(&mut self) -> &LevelGraph {
+        self.level_graph
+            .get_or_insert_with(|| LevelGraph::new(&self.filesystem))
+    }
+
+    /// Records that `completed_level` was just left through an exit into the current level.
+    pub fn record_level_completed(&mut self, completed_level: String) {
+        self.progress.completed_levels.insert(completed_level);
+
+        self.record_level_reached(self.level_name.clone());
+    }
+
+    pub fn record_level_reached(&mut self, level: String) {
+        let depth = self.level_graph().depth(&level);
+        let furthest_depth = self
+            .progress
+            .furthest_level
+            .clone()
+            .and_then(|furthest| self.level_graph().depth(&furthest));
+
+        if self.progress.furthest_level.is_none() || depth > furthest_depth {
+            self.progress.furthest_level = Some(level.clone());
+        }
+
+        self.progress.last_level = Some(level);
+
+        self.save_progress();
+    }
+
+    pub fn save_progress(&mut self) {
+        if let Err(error) = self.progress.save()
+            && error.kind() != io::ErrorKind::Unsupported
+        {
+            self.show_notice(format!("Couldn't save progress: {error}"));
+        }
+    }
+}
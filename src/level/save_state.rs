@@ -0,0 +1,202 @@
+use core::fmt;
+use std::{
+    fmt::{Display, Formatter},
+    io,
+};
+
+use bincode::error::DecodeError;
+use nalgebra::vector;
+use serde::{Deserialize, Serialize};
+use slotmap::{SecondaryMap, SlotMap};
+
+use crate::{
+    collections::{history::FrameIndex, tile_grid::TileGrid},
+    level::{
+        EntityKey, Level,
+        entity_tracker::EntityTracker,
+        filesystem::{self, LoadLevelError},
+        light_grid::{LightGrid, Pixel},
+    },
+};
+
+pub const QUICKSAVE_FILE: &str = "quicksave.state";
+
+/// Everything needed to resume a level mid-loop, including the state entities skip when the level
+/// itself is saved.
+#[derive(Serialize, Deserialize)]
+pub struct SaveState {
+    pub level_name: String,
+    /// The level as it was when this was saved, so that later edits to it can't break the state.
+    pub level_data: Vec<u8>,
+    pub pixels: TileGrid<Pixel>,
+
+    pub soft_reset_state: SlotMap<EntityKey, EntityTracker>,
+    pub soft_reset_runtime_states: SecondaryMap<EntityKey, Vec<u8>>,
+    pub entities: SlotMap<EntityKey, EntityTracker>,
+    pub runtime_states: SecondaryMap<EntityKey, Vec<u8>>,
+
+    pub frame: FrameIndex,
+    pub fade_out_frame: Option<FrameIndex>,
+    pub restart_frame: Option<FrameIndex>,
+}
+
+fn runtime_states(
+    entities: &SlotMap<EntityKey, EntityTracker>,
+) -> SecondaryMap<EntityKey, Vec<u8>> {
+    entities
+        .iter()
+        .map(|(key, entity)| (key, entity.inner.save_runtime_state()))
+        .filter(|(_, state)| !state.is_empty())
+        .collect()
+}
+
+fn load_runtime_states(
+    entities: &mut SlotMap<EntityKey, EntityTracker>,
+    states: &SecondaryMap<EntityKey, Vec<u8>>,
+) -> Result<(), DecodeError> {
+    for (key, state) in states {
+        if let Some(entity) = entities.get_mut(key) {
+            entity.inner.load_runtime_state(state)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Level {
+    pub fn save_state(&mut self) -> Vec<u8> {
+        let level_data = match &self.level_data {
+            Some(level_data) => level_data.clone(),
+            None => self.save(),
+        };
+
+        let state = SaveState {
+            level_name: self.level_name.clone(),
+            level_data,
+            pixels: self.light_grid.pixels().clone(),
+
+            soft_reset_runtime_states: runtime_states(&self.soft_reset_state),
+            soft_reset_state: self.soft_reset_state.clone(),
+            runtime_states: runtime_states(&self.entities),
+            entities: self.entities.clone(),
+
+            frame: self.frame,
+            fade_out_frame: self.fade_out_frame,
+            restart_frame: self.restart_frame,
+        };
+
+        bincode::serde::encode_to_vec(&state, bincode::config::standard()).unwrap()
+    }
+
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), LoadStateError> {
+        let (mut state, _): (SaveState, _) =
+            bincode::serde::decode_from_slice(data, bincode::config::standard())?;
+
+        load_runtime_states(
+            &mut state.soft_reset_state,
+            &state.soft_reset_runtime_states,
+        )?;
+        load_runtime_states(&mut state.entities, &state.runtime_states)?;
+
+        self.level_name = state.level_name;
+        self.level_data = Some(state.level_data);
+        self.load_from_level_data()?;
+
+        self.light_grid = LightGrid::from_pixels(state.pixels);
+
+        self.soft_reset_state = state.soft_reset_state;
+        self.entities = state.entities;
+
+        self.input_readers.clear();
+
+        for (key, entity) in &self.entities {
+            if entity.inner.should_recieve_inputs() {
+                self.input_readers.push(key);
+            }
+        }
+
+        self.frame = state.frame;
+        self.fade_out_frame = state.fade_out_frame;
+        self.restart_frame = state.restart_frame;
+        self.the_end = None;
+
+        for (_, entity) in &mut self.entities {
+            entity.inner.update_view_area(&mut self.light_grid);
+        }
+
+        self.mouse_moved(self.mouse_position, vector![0.0, 0.0]);
+
+        Ok(())
+    }
+
+    /// The quicksave is kept in memory as well, since it can't be written to disk on every
+    /// platform.
+    pub fn quicksave(&mut self) {
+        let state = self.save_state();
+
+        self.show_notice(match filesystem::write_save_file(QUICKSAVE_FILE, &state) {
+            Ok(()) => "Quicksaved".to_string(),
+            Err(error) => format!("Quicksaved, but it couldn't be written to disk: {error}"),
+        });
+
+        self.quicksave = Some(state);
+    }
+
+    pub fn quickload(&mut self) {
+        let state = match self.quicksave.take() {
+            Some(state) => Ok(state),
+            None => filesystem::load_save_file(QUICKSAVE_FILE),
+        };
+
+        let result = state.map_err(LoadStateError::from).and_then(|state| {
+            let result = self.load_state(&state);
+            self.quicksave = Some(state);
+            result
+        });
+
+        if result.is_ok() {
+            // A run can't be timed fairly once it has been rewound.
+            self.speedrun.ticks = None;
+        }
+
+        self.show_notice(match result {
+            Ok(()) => "Quickloaded".to_string(),
+            Err(error) => format!("Couldn't quickload: {error}"),
+        });
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadStateError {
+    Invalid(DecodeError),
+    LoadLevelError(LoadLevelError),
+    IoError(io::Error),
+}
+
+impl From<DecodeError> for LoadStateError {
+    fn from(error: DecodeError) -> Self {
+        LoadStateError::Invalid(error)
+    }
+}
+
+impl From<LoadLevelError> for LoadStateError {
+    fn from(error: LoadLevelError) -> Self {
+        LoadStateError::LoadLevelError(error)
+    }
+}
+
+impl From<io::Error> for LoadStateError {
+    fn from(error: io::Error) -> Self {
+        LoadStateError::IoError(error)
+    }
+}
+
+impl Display for LoadStateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadStateError::Invalid(error) => write!(f, "Invalid save state: {error}"),
+            LoadStateError::LoadLevelError(error) => write!(f, "{error}"),
+            LoadStateError::IoError(error) => write!(f, "{error}"),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use macroquad::{color::colors, text};
+use nalgebra::point;
+
+use crate::{
+    collections::history::FrameIndex,
+    level::{
+        Level,
+        filesystem::{self, SAVES_DIRECTORY},
+        hud::{TEXT_LINE_HEIGHT, draw_text_line, format_time},
+        progress::FIRST_LEVEL,
+    },
+};
+
+/// The personal best, as `level = ticks` lines.
+pub const SPLITS_FILE: &str = "splits.txt";
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Split {
+    /// The level that was left.
+    pub level: String,
+    /// Ticks since the start of the run.
+    pub ticks: FrameIndex,
+}
+
+/// Splits of a whole run, from starting `FIRST_LEVEL` until the end sequence.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Splits {
+    pub splits: Vec<Split>,
+}
+
+impl Splits {
+    /// Lines that can't be parsed are skipped.
+    pub fn parse(text: &str) -> Splits {
+        let splits = text
+            .lines()
+            .filter_map(|line| {
+                let line = line.split('#').next().unwrap_or_default();
+                let (level, ticks) = line.split_once('=')?;
+
+                Some(Split {
+                    level: level.trim().to_owned(),
+                    ticks: ticks.trim().parse().ok()?,
+                })
+            })
+            .collect();
+
+        Splits { splits }
+    }
+
+    pub fn load() -> Option<Splits> {
+        let data = filesystem::load_save_file(SPLITS_FILE).ok()?;
+
+        Some(Splits::parse(&String::from_utf8_lossy(&data)))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        filesystem::write_save_file(SPLITS_FILE, self.to_string().as_bytes())
+    }
+
+    pub fn total(&self) -> Option<FrameIndex> {
+        self.splits.last().map(|split| split.ticks)
+    }
+}
+
+impl Display for Splits {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# level = ticks since the start of the run")?;
+
+        for split in &self.splits {
+            writeln!(f, "{} = {}", split.level, split.ticks)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Counts update ticks across level transitions, from starting `FIRST_LEVEL` to the end.
+#[derive(Clone, Default, Debug)]
+pub struct SpeedrunTimer {
+    pub overlay_active: bool,
+    /// `None` when no run is going, such as after using the level select.
+    pub ticks: Option<FrameIndex>,
+    pub splits: Splits,
+    pub finished: bool,
+    /// Set when the time control is used during the run. It's still timed, but can't become the
+    /// personal best.
+    pub assisted: bool,
+    pub personal_best: Option<Splits>,
+}
+
+impl SpeedrunTimer {
+    pub fn new() -> SpeedrunTimer {
+        SpeedrunTimer {
+            personal_best: Splits::load(),
+            ..Default::default()
+        }
+    }
+
+    /// How far ahead (negative) or behind (positive) of the personal best a split is.
+    pub fn delta(&self, index: usize) -> Option<i64> {
+        let split = self.splits.splits.get(index)?;
+        let best = self.personal_best.as_ref()?.splits.get(index)?;
+
+        (split.level == best.level).then(|| split.ticks as i64 - best.ticks as i64)
+    }
+}
+
+impl Level {
+    /// Starts a new run if `FIRST_LEVEL` was just started from the title screen or the level
+    /// select, and abandons the current one if any other level was. Restarting a level doesn't
+    /// affect the run.
+    pub fn restart_speedrun(&mut self) {
+        let timer = &mut self.speedrun;
+
+        timer.splits = Splits::default();
+        timer.finished = false;
+        timer.assisted = !self.time_control.is_normal();
+        timer.ticks = (self.level_name == FIRST_LEVEL).then_some(0);
+    }
+
+    pub fn update_speedrun(&mut self) {
+        if let Some(ticks) = &mut self.speedrun.ticks
+            && !self.speedrun.finished
+        {
+            *ticks += 1;
+        }
+    }
+
+    pub fn record_split(&mut self, level: String) {
+        let timer = &mut self.speedrun;
+
+        if let Some(ticks) = timer.ticks
+            && !timer.finished
+        {
+            timer.splits.splits.push(Split { level, ticks });
+        }
+    }
+
+    /// Records the last split, and saves the run if it beat the personal best.
+    pub fn finish_speedrun(&mut self) {
+        if self.speedrun.ticks.is_none() || self.speedrun.finished {
+            return;
+        }
+
+        self.record_split(self.level_name.clone());
+        self.speedrun.finished = true;
+
+        let timer = &self.speedrun;
+
+        if timer.assisted {
+            return;
+        }
+
+        let is_personal_best = match (timer.splits.total(), &timer.personal_best) {
+            (Some(total), Some(best)) => best.total().is_none_or(|best| total < best),
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if is_personal_best {
+            self.speedrun.personal_best = Some(self.speedrun.splits.clone());
+
+            match self.speedrun.splits.save() {
+                Ok(()) => self.show_notice("New personal best".to_owned()),
+                Err(error) if error.kind() == io::ErrorKind::Unsupported => {
+                    self.show_notice("New personal best".to_owned());
+                }
+                Err(error) => self.show_notice(format!(
+                    "New personal best, but it couldn't be saved to {SAVES_DIRECTORY}: {error}"
+                )),
+            }
+        }
+    }
+
+    pub fn draw_speedrun_timer(&self) {
+        let timer = &self.speedrun;
+        let screen_rect = crate::screen_rect();
+
+        let mut lines = Vec::new();
+
+        for (i, split) in timer.splits.splits.iter().enumerate() {
+            let (delta, color) = match timer.delta(i) {
+                Some(delta) if delta < 0 => (
+                    format!(" -{}", format_time(-delta as FrameIndex)),
+                    colors::GREEN,
+                ),
+                Some(delta) => (
+                    format!(" +{}", format_time(delta as FrameIndex)),
+                    colors::RED,
+                ),
+                None => (String::new(), colors::LIGHTGRAY),
+            };
+
+            lines.push((
+                format!("{} {}{delta}", split.level, format_time(split.ticks)),
+                color,
+            ));
+        }
+
+        lines.push(match timer.ticks {
+            Some(ticks) if timer.assisted => {
+                (format!("{} assisted", format_time(ticks)), colors::GRAY)
+            }
+            Some(ticks) if timer.finished => (format_time(ticks), colors::YELLOW),
+            Some(ticks) => (format_time(ticks), colors::WHITE),
+            None => (format!("Start {FIRST_LEVEL} to time a run"), colors::GRAY),
+        });
+
+        if let Some(best) = timer.personal_best.as_ref().and_then(Splits::total) {
+            lines.push((format!("Best {}", format_time(best)), colors::GRAY));
+        }
+
+        let mut y = screen_rect.y + 8.0;
+
+        for (line, color) in lines {
+            let width = text::measure_text(&line, None, 8, 1.0).width;
+
+            draw_text_line(
+                &line,
+                point![screen_rect.x + screen_rect.w - width - 4.0, y],
+                color,
+            );
+            y += TEXT_LINE_HEIGHT;
+        }
+    }
+}
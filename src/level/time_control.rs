@@ -0,0 +1,120 @@
+use macroquad::color::colors;
+use nalgebra::point;
+
+use crate::level::{Level, MAX_UPDATES_PER_TICK, UPDATE_DT, hud::draw_text_line};
+
+/// The speeds stepped through by `Action::TimeSlower` and `Action::TimeFaster`, from slowest to
+/// fastest.
+pub const TIME_SCALES: [f64; 7] = [0.125, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// Slows down, speeds up, or pauses the game for debugging. Only how many updates run each frame
+/// changes, every update still moves the level forward by exactly `UPDATE_DT`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimeControl {
+    /// How many updates run for every `UPDATE_DT` of real time.
+    pub scale: f64,
+    pub paused: bool,
+    /// Updates left to run while paused, one per frame.
+    pub steps: usize,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            paused: false,
+            steps: 0,
+        }
+    }
+}
+
+impl TimeControl {
+    pub fn is_normal(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// How many updates `dt` seconds of real time are worth, ignoring pausing.
+    pub fn scaled_updates(&self, dt: f64) -> f64 {
+        dt / UPDATE_DT * self.scale
+    }
+
+    /// Fast forwarding is allowed to fall behind by as many frames as normal speed is.
+    pub fn max_updates_per_tick(&self) -> usize {
+        MAX_UPDATES_PER_TICK * self.scale.ceil().max(1.0) as usize
+    }
+
+    /// Whether a queued step should run this frame, using it up if so.
+    pub fn take_step(&mut self) -> bool {
+        if self.steps > 0 {
+            self.steps -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves to the next slower speed in `TIME_SCALES`, or stays at the slowest.
+    pub fn slower(&mut self) {
+        self.scale = TIME_SCALES
+            .into_iter()
+            .rev()
+            .find(|&scale| scale < self.scale)
+            .unwrap_or(TIME_SCALES[0]);
+    }
+
+    /// Moves to the next faster speed in `TIME_SCALES`, or stays at the fastest.
+    pub fn faster(&mut self) {
+        self.scale = TIME_SCALES
+            .into_iter()
+            .find(|&scale| scale > self.scale)
+            .unwrap_or(TIME_SCALES[TIME_SCALES.len() - 1]);
+    }
+
+    /// Pauses if needed, then queues `steps` more updates.
+    pub fn step(&mut self, steps: usize) {
+        self.paused = true;
+        self.steps += steps;
+    }
+}
+
+impl Level {
+    /// Whether the time control applies right now, rather than the editor or a menu being used.
+    pub fn is_simulating(&self) -> bool {
+        !self.level_editor_active && !self.level_select.active
+    }
+
+    /// Changing the speed of the game marks the speedrun as assisted, so it can't set a personal
+    /// best.
+    pub fn set_time_control(&mut self, time_control: TimeControl) {
+        self.time_control = time_control;
+
+        if !time_control.is_normal() {
+            self.speedrun.assisted = true;
+        }
+    }
+
+    pub fn time_control_text(&self) -> String {
+        let time_control = &self.time_control;
+
+        match (time_control.paused, time_control.steps) {
+            (true, 0) => format!("Paused at frame {}", self.frame),
+            (true, steps) => format!("Stepping, {steps} frames left"),
+            (false, _) => format!("Speed x{}", time_control.scale),
+        }
+    }
+
+    /// Shown whenever the game isn't running at normal speed, so it isn't forgotten about.
+    pub fn draw_time_control(&self) {
+        if self.time_control.is_normal() {
+            return;
+        }
+
+        let screen_rect = crate::screen_rect();
+
+        draw_text_line(
+            &self.time_control_text(),
+            point![screen_rect.x + 4.0, screen_rect.y + screen_rect.h - 4.0],
+            colors::YELLOW,
+        );
+    }
+}
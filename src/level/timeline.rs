@@ -0,0 +1,196 @@
+use macroquad::{
+    color::{Color, colors},
+    input::KeyCode,
+    shapes,
+    text::{self, TextParams},
+};
+use nalgebra::{Point2, point};
+
+use crate::{
+    collections::history::FrameIndex,
+    input::{self, Action, Binding},
+    level::{
+        Level, UPDATE_TPS,
+        entity_tracker::entity::player::{Player, PlayerState},
+    },
+};
+
+#[derive(Clone, Default, Debug)]
+pub struct Timeline {
+    pub active: bool,
+    pub preview_offset: FrameIndex,
+}
+
+pub const TIMELINE_COLORS: [u32; 6] = [0x7fd4ff, 0xffb347, 0xb39cff, 0x8cff9e, 0xff8cc6, 0xfff07f];
+
+impl Timeline {
+    /// How many frames apart the points along each path are.
+    pub const PATH_STEP: FrameIndex = 2;
+
+    /// How far each press of `Action::TimelineEarlier` or `Action::TimelineLater` moves the
+    /// preview.
+    pub const PREVIEW_STEP: FrameIndex = UPDATE_TPS / 2;
+    pub const MAXIMUM_PREVIEW_OFFSET: FrameIndex = UPDATE_TPS * 30;
+
+    /// How many seconds apart the labeled timestamps are.
+    pub const LABEL_INTERVAL: FrameIndex = 5;
+}
+
+impl Level {
+    pub fn timeline_key_down(&mut self, input: KeyCode) {
+        let bindings = input::key_bindings().clone();
+
+        if bindings.is_bound(Action::TimelineLater, input) {
+            self.timeline.preview_offset = (self.timeline.preview_offset + Timeline::PREVIEW_STEP)
+                .min(Timeline::MAXIMUM_PREVIEW_OFFSET);
+        }
+
+        if bindings.is_bound(Action::TimelineEarlier, input) {
+            self.timeline.preview_offset = self
+                .timeline
+                .preview_offset
+                .saturating_sub(Timeline::PREVIEW_STEP);
+        }
+    }
+
+    /// Draws the path of every past self, with a marker for where they are now and where they
+    /// will be after the preview offset.
+    pub fn draw_timeline(&self) {
+        let past_selves = self
+            .entities
+            .iter()
+            .filter_map(|(_, entity)| entity.inner.as_player())
+            .filter(|player| player.state != PlayerState::Active);
+
+        for (i, player) in past_selves.enumerate() {
+            let color = Color::from_hex(TIMELINE_COLORS[i % TIMELINE_COLORS.len()]);
+
+            self.draw_timeline_path(player, color);
+        }
+
+        let screen_rect = crate::screen_rect();
+
+        let preview = self.timeline.preview_offset as f32 / UPDATE_TPS as f32;
+        let text = {
+            let bindings = input::key_bindings();
+            let first_binding = |action| {
+                bindings
+                    .get(action)
+                    .first()
+                    .map_or_else(|| "unbound".to_owned(), Binding::to_string)
+            };
+
+            format!(
+                "Timeline: +{preview:.1}s ({} and {} to scrub)",
+                first_binding(Action::TimelineEarlier),
+                first_binding(Action::TimelineLater),
+            )
+        };
+
+        let start = point![screen_rect.x + 2.0, screen_rect.y + 8.0];
+        let width = text::measure_text(&text, None, 8, 1.0).width;
+
+        shapes::draw_rectangle(start.x, start.y - 5.0, width, 6.0, colors::BLACK);
+
+        text::draw_text_ex(
+            &text,
+            start.x,
+            start.y,
+            TextParams {
+                font_size: 16,
+                font_scale: 0.5,
+                color: colors::WHITE,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn draw_timeline_path(&self, player: &Player, color: Color) {
+        let range = player.history.range();
+
+        if range.is_empty() {
+            return;
+        }
+
+        let past_color = Color { a: 0.35, ..color };
+
+        let mut previous: Option<Point2<f32>> = None;
+
+        for frame in range
+            .clone()
+            .step_by(Timeline::PATH_STEP)
+            .chain([range.end - 1])
+        {
+            let Some(entry) = player.history.get(frame) else {
+                previous = None;
+                continue;
+            };
+            let position = entry.position;
+
+            if let Some(previous) = previous {
+                shapes::draw_line(
+                    previous.x,
+                    previous.y,
+                    position.x,
+                    position.y,
+                    0.5,
+                    if frame <= self.frame {
+                        past_color
+                    } else {
+                        color
+                    },
+                );
+            }
+
+            previous = Some(position);
+        }
+
+        for frame in range.clone().step_by(UPDATE_TPS) {
+            let Some(entry) = player.history.get(frame) else {
+                continue;
+            };
+            let position = entry.position;
+            let seconds = frame / UPDATE_TPS;
+
+            shapes::draw_rectangle(position.x - 0.5, position.y - 0.5, 1.0, 1.0, color);
+
+            if seconds.is_multiple_of(Timeline::LABEL_INTERVAL) {
+                text::draw_text_ex(
+                    &format!("{seconds}s"),
+                    position.x + 1.0,
+                    position.y - 1.0,
+                    TextParams {
+                        font_size: 16,
+                        font_scale: 0.25,
+                        color,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        let size = player.size.map(|x| x as f32);
+
+        if let Some(entry) = player.history.get(self.frame) {
+            let corner = entry.position - size / 2.0;
+
+            shapes::draw_rectangle_lines(corner.x, corner.y, size.x, size.y, 1.0, colors::WHITE);
+        }
+
+        if self.timeline.preview_offset > 0
+            && let Some(entry) = player
+                .history
+                .get(self.frame + self.timeline.preview_offset)
+        {
+            let corner = entry.position - size / 2.0;
+
+            shapes::draw_rectangle(
+                corner.x,
+                corner.y,
+                size.x,
+                size.y,
+                Color { a: 0.6, ..color },
+            );
+        }
+    }
+}
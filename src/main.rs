@@ -1,3 +1,5 @@
+use std::io;
+
 use macroquad::{
     camera::{self, Camera2D},
     color::colors,
@@ -8,13 +10,24 @@ use macroquad::{
 };
 use nalgebra::{Point2, Vector2, point, vector};
 
-use crate::level::{Level, MAX_UPDATES_PER_TICK, UPDATE_DT, filesystem::FileSystem};
+use crate::{
+    input::{Action, KeyBindings},
+    level::{
+        Level, MAX_UPDATES_PER_TICK, UPDATE_DT, filesystem::FileSystem, progress::FIRST_LEVEL,
+    },
+    menu::{Menu, Screen},
+    settings::Settings,
+};
 
+pub(crate) mod bench;
 #[allow(dead_code)]
 pub(crate) mod collections;
 #[allow(dead_code)]
 pub(crate) mod input;
 pub(crate) mod level;
+pub(crate) mod menu;
+pub(crate) mod overview;
+pub(crate) mod settings;
 
 pub const START_IN_FULLSCREEN: bool = true;
 
@@ -22,7 +35,7 @@ fn config() -> Conf {
     Conf {
         window_title: "Time Travel Stealth Game".to_owned(),
         high_dpi: true,
-        fullscreen: START_IN_FULLSCREEN,
+        fullscreen: Settings::load().fullscreen,
         ..Default::default()
     }
 }
@@ -35,8 +48,22 @@ unsafe extern "C" {
     fn __wasm_call_ctors();
 }
 
-#[macroquad::main(config)]
-async fn main() {
+fn main() {
+    // Benchmarks and overviews don't need a window.
+    if let Some(filter) = bench::requested() {
+        bench::run(&filter);
+        return;
+    }
+
+    if let Some(request) = overview::requested() {
+        overview::run(&request);
+        return;
+    }
+
+    macroquad::Window::from_config(config(), run());
+}
+
+async fn run() {
     #[cfg(target_family = "wasm")]
     unsafe {
         __wasm_call_ctors();
@@ -96,7 +123,8 @@ async fn main() {
 }
 
 pub(crate) struct State {
-    fullscreen: bool,
+    settings: Settings,
+    menu: Menu,
 
     level: Level,
     update_time: f64,
@@ -104,43 +132,117 @@ pub(crate) struct State {
 
 impl State {
     fn new() -> Self {
-        let mut level = Level::new("start".to_owned());
+        // Loaded first, since players are given their inputs when the level is.
+        let settings = Settings::load();
+        *input::key_bindings() = settings.key_bindings.clone();
+        *input::AIM_MODE.lock().unwrap() = settings.aim_mode;
+
+        let mut level = Level::new(FIRST_LEVEL.to_owned());
 
         if matches!(level.filesystem, FileSystem::Direct { .. })
-            && level.filesystem.load("start").is_err()
+            && level.filesystem.load(FIRST_LEVEL).is_err()
         {
             let data = level.save();
             level
                 .filesystem
-                .save("start", &data)
+                .save(FIRST_LEVEL, &data)
                 .expect("Saving the default level should be valid");
         }
 
-        level.reset().expect("Default level should be valid");
+        // Resume wherever the player left off, unless that level has since gone missing.
+        let resumed = level.progress.last_level.clone().is_some_and(|last_level| {
+            level.level_name = last_level;
+            level.reset().is_ok()
+        });
+
+        if !resumed {
+            level.level_name = FIRST_LEVEL.to_owned();
+            level.reset().expect("Default level should be valid");
+        }
+
+        level.restart_speedrun();
         level.step_at_level_start();
 
+        level.occlude_wall_shadows = settings.occlude_wall_shadows;
+
         State {
-            fullscreen: START_IN_FULLSCREEN,
+            settings,
+            menu: Menu::default(),
 
             level,
             update_time: 0.0,
         }
     }
+
+    pub fn apply_settings(&mut self) {
+        window::set_fullscreen(self.settings.fullscreen);
+        self.level.occlude_wall_shadows = self.settings.occlude_wall_shadows;
+        *input::AIM_MODE.lock().unwrap() = self.settings.aim_mode;
+    }
+
+    /// Takes effect straight away, including for players that are already in the level.
+    pub fn set_key_bindings(&mut self, key_bindings: KeyBindings) {
+        *input::key_bindings() = key_bindings.clone();
+        self.settings.key_bindings = key_bindings;
+        self.level.apply_key_bindings();
+
+        self.save_settings();
+    }
+
+    pub fn save_settings(&mut self) {
+        if let Err(error) = self.settings.save()
+            && error.kind() != io::ErrorKind::Unsupported
+        {
+            self.level
+                .show_notice(format!("Couldn't save settings: {error}"));
+        }
+    }
 }
 
 impl State {
     pub const SCREEN_HEIGHT: f32 = 256.0;
 
     fn update(&mut self, dt: f64) {
-        self.update_time += dt / UPDATE_DT;
-
-        for _ in 0..MAX_UPDATES_PER_TICK.min(self.update_time.floor() as usize) {
-            self.level.update();
+        let simulating = self.menu.screen == Screen::Playing && self.level.is_simulating();
+        let time_control = &mut self.level.time_control;
+
+        let updates = if !simulating {
+            self.update_time += dt / UPDATE_DT;
+            MAX_UPDATES_PER_TICK.min(self.update_time.floor() as usize)
+        } else if time_control.paused {
+            let steps = time_control.take_step() as usize;
+
+            // Notices still fade in real time, or they'd stay up for as long as the pause lasts.
+            self.level.age_notice(dt);
+
+            // Left at 1 once the steps are done, so everything is drawn where the last one left it.
+            self.update_time = 1.0 + steps as f64;
+            steps
+        } else {
+            self.update_time += time_control.scaled_updates(dt);
+            time_control
+                .max_updates_per_tick()
+                .min(self.update_time.floor() as usize)
+        };
+
+        for _ in 0..updates {
+            if self.menu.screen == Screen::Playing {
+                self.level.update();
+            } else {
+                self.level.update_notice();
+            }
 
             self.update_time -= 1.0;
         }
 
         self.update_time = self.update_time.min(1.0);
+
+        // Nothing moves while the game is paused, so there's nothing to draw in between.
+        self.level.tick_fraction = if self.menu.screen == Screen::Playing {
+            self.update_time
+        } else {
+            1.0
+        };
     }
 
     fn draw(&mut self) {
@@ -150,23 +252,47 @@ impl State {
         camera.zoom.y *= -1.0;
         camera::set_camera(&camera);
 
-        self.level.draw();
+        if self.menu.screen != Screen::Title {
+            self.level.draw();
+        }
+
+        self.draw_menu();
+
+        self.level.draw_notice();
     }
 
     fn text_input_event(&mut self, input: char) {
-        self.level.text_input(input);
+        if self.menu.screen == Screen::Playing {
+            self.level.text_input(input);
+        }
     }
 
     fn key_down_event(&mut self, input: KeyCode) {
-        self.level.key_down(input);
+        let bindings = input::key_bindings().clone();
 
         match input {
-            KeyCode::F11 => {
-                self.fullscreen ^= true;
+            _ if self.menu.rebinding.is_some() => {
+                self.menu_key_down(input);
+            }
+            _ if bindings.is_bound(Action::Fullscreen, input) => {
+                self.settings.fullscreen ^= true;
 
-                window::set_fullscreen(self.fullscreen);
+                self.apply_settings();
+                self.save_settings();
+            }
+            _ if bindings.is_bound(Action::Pause, input)
+                && self.menu.screen == Screen::Playing
+                && !self.level.level_editor_active
+                && !self.level.level_select.active =>
+            {
+                self.open_screen(Screen::Paused);
+            }
+            _ if self.menu.screen == Screen::Playing => {
+                self.level.key_down(input);
+            }
+            _ => {
+                self.menu_key_down(input);
             }
-            _ => (),
         }
     }
 
@@ -175,6 +301,10 @@ impl State {
     }
 
     fn mouse_button_down_event(&mut self, button: MouseButton, position: Point2<f32>) {
+        if self.menu.screen != Screen::Playing {
+            return;
+        }
+
         self.level
             .mouse_down(button, screen_to_world(position).map(|x| x as f64));
     }
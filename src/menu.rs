@@ -0,0 +1,363 @@
+use macroquad::{
+    color::{Color, colors},
+    input::KeyCode,
+    shapes, text, window,
+};
+use nalgebra::point;
+
+use crate::{
+    State,
+    input::{self, Action, AimMode, Binding, KeyBindings},
+    level::{
+        entity_tracker::entity::GameAction,
+        hud::{TEXT_LINE_HEIGHT, draw_text_line},
+        progress::FIRST_LEVEL,
+    },
+};
+
+pub const GAME_TITLE: &str = "Look Away";
+
+/// Which screen is shown on top of the level. The level only updates while `Playing`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Screen {
+    Title,
+    Playing,
+    Paused,
+    Settings { from_title: bool },
+    Controls { from_title: bool },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuItem {
+    Continue,
+    NewGame,
+    LevelSelect,
+    Resume,
+    RestartLevel,
+    Settings,
+    QuitToTitle,
+    Quit,
+    Fullscreen,
+    WallShadows,
+    Aiming,
+    Controls,
+    Binding(Action),
+    ResetControls,
+    Back,
+}
+
+#[derive(Clone, Debug)]
+pub struct Menu {
+    pub screen: Screen,
+    pub selected: usize,
+    /// Waiting for a key to bind to this action.
+    pub rebinding: Option<Action>,
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self {
+            screen: Screen::Title,
+            selected: 0,
+            rebinding: None,
+        }
+    }
+}
+
+impl Menu {
+    pub fn items(&self, has_progress: bool) -> Vec<MenuItem> {
+        match self.screen {
+            Screen::Title => {
+                let mut items = vec![MenuItem::Continue];
+
+                if has_progress {
+                    items.push(MenuItem::NewGame);
+                }
+
+                items.extend([MenuItem::LevelSelect, MenuItem::Settings]);
+
+                if !cfg!(target_family = "wasm") {
+                    items.push(MenuItem::Quit);
+                }
+
+                items
+            }
+            Screen::Playing => Vec::new(),
+            Screen::Paused => vec![
+                MenuItem::Resume,
+                MenuItem::RestartLevel,
+                MenuItem::LevelSelect,
+                MenuItem::Settings,
+                MenuItem::QuitToTitle,
+            ],
+            Screen::Settings { .. } => vec![
+                MenuItem::Fullscreen,
+                MenuItem::WallShadows,
+                MenuItem::Aiming,
+                MenuItem::Controls,
+                MenuItem::Back,
+            ],
+            Screen::Controls { .. } => Action::ALL
+                .into_iter()
+                .map(MenuItem::Binding)
+                .chain([MenuItem::ResetControls, MenuItem::Back])
+                .collect(),
+        }
+    }
+}
+
+impl State {
+    pub fn open_screen(&mut self, screen: Screen) {
+        self.menu.screen = screen;
+        self.menu.selected = 0;
+    }
+
+    fn menu_items(&self) -> Vec<MenuItem> {
+        self.menu.items(self.level.progress.last_level.is_some())
+    }
+
+    pub fn menu_key_down(&mut self, input: KeyCode) {
+        // Escape cancels, so it can only be bound in the settings file.
+        if let Some(action) = self.menu.rebinding.take() {
+            if input != KeyCode::Escape {
+                let mut key_bindings = self.settings.key_bindings.clone();
+                key_bindings
+                    .bindings
+                    .insert(action, vec![Binding::Key(input)]);
+
+                self.set_key_bindings(key_bindings);
+            }
+
+            return;
+        }
+
+        let items = self.menu_items();
+        let bindings = input::key_bindings().clone();
+
+        match input {
+            _ if bindings.is_bound(Action::MoveUp, input) => {
+                self.menu.selected = self.menu.selected.saturating_sub(1);
+            }
+            _ if bindings.is_bound(Action::MoveDown, input) => {
+                self.menu.selected = (self.menu.selected + 1).min(items.len().saturating_sub(1));
+            }
+            _ if bindings.is_bound(Action::MoveLeft, input) => {
+                if let Some(&item) = items.get(self.menu.selected) {
+                    self.adjust_menu_item(item, -1);
+                }
+            }
+            _ if bindings.is_bound(Action::MoveRight, input) => {
+                if let Some(&item) = items.get(self.menu.selected) {
+                    self.adjust_menu_item(item, 1);
+                }
+            }
+            KeyCode::Enter | KeyCode::Space => {
+                if let Some(&item) = items.get(self.menu.selected) {
+                    self.activate_menu_item(item);
+                }
+            }
+            _ if input == KeyCode::Escape || bindings.is_bound(Action::Pause, input) => {
+                match self.menu.screen {
+                    Screen::Paused => self.open_screen(Screen::Playing),
+                    Screen::Settings { .. } | Screen::Controls { .. } => {
+                        self.activate_menu_item(MenuItem::Back);
+                    }
+                    Screen::Title | Screen::Playing => (),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn activate_menu_item(&mut self, item: MenuItem) {
+        match item {
+            MenuItem::Continue | MenuItem::Resume => {
+                self.open_screen(Screen::Playing);
+            }
+            MenuItem::NewGame => {
+                self.level.level_name = FIRST_LEVEL.to_owned();
+                self.level.level_data = None;
+                self.level.the_end = None;
+
+                if self.evaluate_menu_action(GameAction::HardReset) {
+                    self.level.restart_speedrun();
+                    self.level.record_level_reached(FIRST_LEVEL.to_owned());
+                }
+            }
+            MenuItem::LevelSelect => {
+                self.open_screen(Screen::Playing);
+                self.level.open_level_select();
+            }
+            MenuItem::RestartLevel => {
+                self.evaluate_menu_action(GameAction::HardReset);
+            }
+            MenuItem::Settings => {
+                let from_title = self.menu.screen == Screen::Title;
+
+                self.open_screen(Screen::Settings { from_title });
+            }
+            MenuItem::QuitToTitle => {
+                self.open_screen(Screen::Title);
+            }
+            MenuItem::Quit => {
+                window::miniquad::window::quit();
+            }
+            MenuItem::Fullscreen | MenuItem::WallShadows | MenuItem::Aiming => {
+                self.adjust_menu_item(item, 1);
+            }
+            MenuItem::Controls => {
+                if let Screen::Settings { from_title } = self.menu.screen {
+                    self.open_screen(Screen::Controls { from_title });
+                }
+            }
+            MenuItem::Binding(action) => {
+                self.menu.rebinding = Some(action);
+            }
+            MenuItem::ResetControls => {
+                self.set_key_bindings(KeyBindings::default());
+            }
+            MenuItem::Back => {
+                let back = match self.menu.screen {
+                    Screen::Settings { from_title: true } => {
+                        Some((Screen::Title, MenuItem::Settings))
+                    }
+                    Screen::Settings { from_title: false } => {
+                        Some((Screen::Paused, MenuItem::Settings))
+                    }
+                    Screen::Controls { from_title } => {
+                        Some((Screen::Settings { from_title }, MenuItem::Controls))
+                    }
+                    Screen::Title | Screen::Playing | Screen::Paused => None,
+                };
+
+                if let Some((screen, item)) = back {
+                    self.open_screen(screen);
+                    self.menu.selected = self
+                        .menu_items()
+                        .iter()
+                        .position(|&other| other == item)
+                        .unwrap_or(0);
+                }
+            }
+        }
+    }
+
+    fn adjust_menu_item(&mut self, item: MenuItem, steps: i32) {
+        match item {
+            MenuItem::Fullscreen => {
+                self.settings.fullscreen ^= true;
+            }
+            MenuItem::WallShadows => {
+                self.settings.occlude_wall_shadows ^= true;
+            }
+            MenuItem::Aiming => {
+                self.settings.aim_mode = self.settings.aim_mode.cycle(steps);
+            }
+            _ => return,
+        }
+
+        self.apply_settings();
+        self.save_settings();
+    }
+
+    fn evaluate_menu_action(&mut self, action: GameAction) -> bool {
+        match self.level.evaluate_game_action(&action) {
+            Ok(()) => {
+                self.open_screen(Screen::Playing);
+                true
+            }
+            Err(error) => {
+                self.level.show_notice(format!("{error}"));
+                false
+            }
+        }
+    }
+
+    fn menu_item_label(&self, item: MenuItem) -> String {
+        let on_off = |value| if value { "on" } else { "off" };
+
+        match item {
+            MenuItem::Continue if self.level.progress.last_level.is_some() => {
+                format!("Continue ({})", self.level.level_name)
+            }
+            MenuItem::Continue => "Play".to_owned(),
+            MenuItem::NewGame => "New game".to_owned(),
+            MenuItem::LevelSelect => "Level select".to_owned(),
+            MenuItem::Resume => "Resume".to_owned(),
+            MenuItem::RestartLevel => "Restart level".to_owned(),
+            MenuItem::Settings => "Settings".to_owned(),
+            MenuItem::QuitToTitle => "Quit to title".to_owned(),
+            MenuItem::Quit => "Quit".to_owned(),
+            MenuItem::Fullscreen => format!("Fullscreen: {}", on_off(self.settings.fullscreen)),
+            MenuItem::WallShadows => format!(
+                "Wall shadows: {}",
+                on_off(self.settings.occlude_wall_shadows)
+            ),
+            MenuItem::Aiming => format!(
+                "Aiming: {}",
+                match self.settings.aim_mode {
+                    AimMode::Mouse => "mouse",
+                    AimMode::RotateKeys => "rotate keys",
+                    AimMode::Movement => "movement direction",
+                }
+            ),
+            MenuItem::Controls => "Controls".to_owned(),
+            MenuItem::Binding(action) if self.menu.rebinding == Some(action) => {
+                format!("{}: press a key, or escape to cancel", action.name())
+            }
+            MenuItem::Binding(action) => format!(
+                "{}: {}",
+                action.name(),
+                self.settings.key_bindings.bindings_to_string(action)
+            ),
+            MenuItem::ResetControls => "Reset to defaults".to_owned(),
+            MenuItem::Back => "Back".to_owned(),
+        }
+    }
+
+    pub fn draw_menu(&self) {
+        let screen_rect = crate::screen_rect();
+
+        let (title, background) = match self.menu.screen {
+            Screen::Playing => return,
+            Screen::Title => (GAME_TITLE, colors::BLACK),
+            Screen::Paused => ("Paused", Color::new(0.0, 0.0, 0.0, 0.6)),
+            Screen::Settings { from_title: true } => ("Settings", colors::BLACK),
+            Screen::Settings { from_title: false } => ("Settings", Color::new(0.0, 0.0, 0.0, 0.6)),
+            Screen::Controls { from_title: true } => ("Controls", colors::BLACK),
+            Screen::Controls { from_title: false } => ("Controls", Color::new(0.0, 0.0, 0.0, 0.6)),
+        };
+
+        shapes::draw_rectangle(
+            screen_rect.x,
+            screen_rect.y,
+            screen_rect.w,
+            screen_rect.h,
+            background,
+        );
+
+        let items = self.menu_items();
+        let mut y = -(items.len() as f32 + 2.0) * TEXT_LINE_HEIGHT / 2.0;
+
+        let draw_centered = |line: &str, y: f32, color| {
+            let width = text::measure_text(line, None, 8, 1.0).width;
+
+            draw_text_line(line, point![-width / 2.0, y], color);
+        };
+
+        draw_centered(title, y, colors::WHITE);
+        y += TEXT_LINE_HEIGHT * 2.0;
+
+        for (i, &item) in items.iter().enumerate() {
+            let label = self.menu_item_label(item);
+
+            if i == self.menu.selected {
+                draw_centered(&format!("> {label} <"), y, colors::YELLOW);
+            } else {
+                draw_centered(&label, y, colors::LIGHTGRAY);
+            }
+
+            y += TEXT_LINE_HEIGHT;
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! Draws overviews of levels on the CPU and saves them as PNGs without opening a window, with
+//! `cargo run -- --overview <level> ?output [--wires] [--view-cones]`. The level can be the name of
+//! a level or the path to a level file.
+
+use std::{fs, mem, path::Path};
+
+use macroquad::{
+    color::{Color, colors},
+    math::{Rect, Vec2, vec2},
+    models::Mesh,
+    texture::{DrawTextureParams, Image},
+};
+
+use crate::level::{Level, TILE_SIZE, tile::TILE_KINDS};
+
+pub const OVERVIEW_FLAG: &str = "--overview";
+pub const WIRES_FLAG: &str = "--wires";
+pub const VIEW_CONES_FLAG: &str = "--view-cones";
+
+pub const DEFAULT_OUTPUT: &str = "overview.png";
+
+/// Empty space left around the level's tiles.
+const MARGIN: f32 = TILE_SIZE as f32;
+
+/// Wires that aren't powered yet, which is most of them before the level has been played.
+pub const WIRE_COLOR: Color = Color::new(0.6, 0.6, 0.6, 0.8);
+pub const VIEW_CONE_COLOR: Color = Color::new(0.5, 0.8, 1.0, 0.3);
+pub const LIT_AREA_COLOR: Color = Color::new(1.0, 0.9, 0.5, 0.2);
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct OverviewOptions {
+    pub wires: bool,
+    pub view_cones: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct OverviewRequest {
+    pub level: String,
+    pub output: String,
+    pub options: OverviewOptions,
+}
+
+/// Returns what to draw if an overview was asked for on the command line.
+pub fn requested() -> Option<OverviewRequest> {
+    let mut args = std::env::args().skip(1);
+
+    if args.next()? != OVERVIEW_FLAG {
+        return None;
+    }
+
+    let mut options = OverviewOptions::default();
+    let mut paths = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            WIRES_FLAG => options.wires = true,
+            VIEW_CONES_FLAG => options.view_cones = true,
+            _ => paths.push(arg),
+        }
+    }
+
+    let mut paths = paths.into_iter();
+
+    Some(OverviewRequest {
+        level: paths.next().unwrap_or_default(),
+        output: paths.next().unwrap_or_else(|| DEFAULT_OUTPUT.to_owned()),
+        options,
+    })
+}
+
+pub fn run(request: &OverviewRequest) {
+    if request.level.is_empty() {
+        eprintln!("Usage: {OVERVIEW_FLAG} <level> ?output [{WIRES_FLAG}] [{VIEW_CONES_FLAG}]");
+        return;
+    }
+
+    let mut level = Level::new(request.level.clone());
+
+    if Path::new(&request.level).is_file() {
+        match fs::read(&request.level) {
+            Ok(data) => level.level_data = Some(data),
+            Err(error) => {
+                eprintln!("{}: couldn't be read: {error}", request.level);
+                return;
+            }
+        }
+    }
+
+    if let Err(error) = level.reset() {
+        eprintln!("{}: couldn't be loaded: {error}", request.level);
+        return;
+    }
+
+    // View and light areas are only traced during an update, the same as when a level starts.
+    level.step_at_level_start();
+
+    let texture_atlas = Image::from_file_with_format(crate::TEXTURE_ATLAS, None).unwrap();
+
+    let image = draw_overview(&level, texture_atlas, request.options);
+
+    // `export_png` panics if it can't write the file, so make sure it can first.
+    if let Err(error) = fs::File::create(&request.output) {
+        eprintln!("{}: couldn't be written: {error}", request.output);
+        return;
+    }
+
+    // `export_png` flips images upside down, since it expects them to be read back from the GPU.
+    flipped(&image).export_png(&request.output);
+
+    println!(
+        "{}: saved a {}x{} overview to {}",
+        request.level, image.width, image.height, request.output
+    );
+}
+
+/// Draws the level as it is right now, tiles first and then every entity.
+pub fn draw_overview(level: &Level, texture_atlas: Image, options: OverviewOptions) -> Image {
+    let bounds = level.tile_grid.bounds();
+
+    let mut canvas = Canvas::new(
+        Rect::new(
+            (bounds.left() * TILE_SIZE) as f32 - MARGIN,
+            (bounds.top() * TILE_SIZE) as f32 - MARGIN,
+            (bounds.size.x as isize * TILE_SIZE) as f32 + MARGIN * 2.0,
+            (bounds.size.y as isize * TILE_SIZE) as f32 + MARGIN * 2.0,
+        ),
+        texture_atlas,
+    );
+
+    {
+        let tile_kinds = TILE_KINDS.lock().unwrap();
+
+        for (index, tile) in level.tile_grid.iter() {
+            let Some(tile) = tile else {
+                continue;
+            };
+
+            canvas.draw_texture_ex(
+                (index.x * TILE_SIZE) as f32,
+                (index.y * TILE_SIZE) as f32,
+                colors::WHITE,
+                DrawTextureParams {
+                    source: Some(tile_kinds[tile.kind].texture_rect()),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    for entity in level.entities.values() {
+        entity.inner.draw_overview(&mut canvas);
+    }
+
+    // Over the entities, since players start inside of elevators.
+    if options.view_cones {
+        for entity in level.entities.values() {
+            if let Some(area) = entity.inner.light_area()
+                && let Some(mesh) = area.mesh(LIT_AREA_COLOR)
+            {
+                canvas.draw_mesh(&mesh, LIT_AREA_COLOR);
+            }
+
+            if let Some(area) = entity.inner.view_area()
+                && let Some(mesh) = area.mesh(VIEW_CONE_COLOR)
+            {
+                canvas.draw_mesh(&mesh, VIEW_CONE_COLOR);
+            }
+        }
+    }
+
+    if options.wires {
+        for (start, end, color) in Level::wires(&level.entities, Some(WIRE_COLOR)) {
+            canvas.draw_line(
+                start.x as f32,
+                start.y as f32,
+                end.x as f32,
+                end.y as f32,
+                2.0,
+                color,
+            );
+        }
+    }
+
+    canvas.image
+}
+
+fn flipped(image: &Image) -> Image {
+    let row_length = image.width as usize * 4;
+
+    Image {
+        bytes: image
+            .bytes
+            .chunks_exact(row_length)
+            .rev()
+            .flatten()
+            .copied()
+            .collect(),
+        width: image.width,
+        height: image.height,
+    }
+}
+
+/// An image drawn to on the CPU, with the same drawing functions as macroquad. Each pixel is drawn
+/// to if its center is covered.
+pub struct Canvas {
+    pub image: Image,
+    /// Where the top left corner of the image is in the level.
+    pub origin: Vec2,
+    pub texture_atlas: Image,
+}
+
+impl Canvas {
+    pub fn new(rect: Rect, texture_atlas: Image) -> Self {
+        Self {
+            image: Image::gen_image_color(
+                rect.w.ceil() as u16,
+                rect.h.ceil() as u16,
+                colors::BLACK,
+            ),
+            origin: vec2(rect.x, rect.y),
+            texture_atlas,
+        }
+    }
+
+    /// Blends `color` over the pixel, if it's in the image.
+    pub fn blend_pixel(&mut self, x: isize, y: isize, color: Color) {
+        if x < 0 || y < 0 || x >= self.image.width as isize || y >= self.image.height as isize {
+            return;
+        }
+
+        let old = self.image.get_pixel(x as u32, y as u32);
+        let alpha = color.a;
+
+        self.image.set_pixel(
+            x as u32,
+            y as u32,
+            Color::new(
+                color.r * alpha + old.r * (1.0 - alpha),
+                color.g * alpha + old.g * (1.0 - alpha),
+                color.b * alpha + old.b * (1.0 - alpha),
+                alpha + old.a * (1.0 - alpha),
+            ),
+        );
+    }
+
+    /// Calls `function` with the center of every pixel within `min` and `max`, in level
+    /// coordinates, and blends in the color it returns.
+    fn fill(&mut self, min: Vec2, max: Vec2, mut function: impl FnMut(Vec2) -> Option<Color>) {
+        let min = (min - self.origin).floor();
+        let max = (max - self.origin).ceil();
+
+        for y in min.y as isize..max.y as isize {
+            for x in min.x as isize..max.x as isize {
+                let center = self.origin + vec2(x as f32 + 0.5, y as f32 + 0.5);
+
+                if let Some(color) = function(center) {
+                    self.blend_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    pub fn draw_rectangle(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        let rect = Rect::new(x, y, w, h);
+
+        self.fill(vec2(x, y), vec2(x + w, y + h), |center| {
+            rect.contains(center).then_some(color)
+        });
+    }
+
+    pub fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        let start = vec2(x1, y1);
+        let end = vec2(x2, y2);
+        let half_thickness = thickness / 2.0;
+
+        self.fill(
+            start.min(end) - Vec2::splat(half_thickness),
+            start.max(end) + Vec2::splat(half_thickness),
+            |center| {
+                let along = end - start;
+                let t = ((center - start).dot(along) / along.length_squared().max(f32::EPSILON))
+                    .clamp(0.0, 1.0);
+
+                (center.distance(start + along * t) <= half_thickness).then_some(color)
+            },
+        );
+    }
+
+    /// Follows `macroquad::texture::draw_texture_ex`, sampling from the texture atlas.
+    pub fn draw_texture_ex(&mut self, x: f32, y: f32, color: Color, params: DrawTextureParams) {
+        let source = params.source.unwrap_or(Rect::new(
+            0.0,
+            0.0,
+            self.texture_atlas.width as f32,
+            self.texture_atlas.height as f32,
+        ));
+
+        let size = params.dest_size.unwrap_or(source.size());
+        let (mut x, mut y, mut w, mut h) = (x, y, size.x, size.y);
+
+        if params.flip_x {
+            x += w;
+            w = -w;
+        }
+
+        if params.flip_y {
+            y += h;
+            h = -h;
+        }
+
+        let pivot = params.pivot.unwrap_or(vec2(x + w / 2.0, y + h / 2.0));
+        let rotation = Vec2::from_angle(params.rotation);
+
+        let corners = [
+            vec2(x, y),
+            vec2(x + w, y),
+            vec2(x + w, y + h),
+            vec2(x, y + h),
+        ]
+        .map(|corner| pivot + rotation.rotate(corner - pivot));
+
+        let min = corners.into_iter().reduce(Vec2::min).unwrap();
+        let max = corners.into_iter().reduce(Vec2::max).unwrap();
+
+        let inverse_rotation = Vec2::from_angle(-params.rotation);
+        // Taken out while drawing, since it's only read from.
+        let texture_atlas = mem::replace(&mut self.texture_atlas, Image::empty());
+
+        self.fill(min, max, |center| {
+            let unrotated = pivot + inverse_rotation.rotate(center - pivot);
+
+            let u = (unrotated.x - x) / w;
+            let v = (unrotated.y - y) / h;
+
+            if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                return None;
+            }
+
+            let texel = texture_atlas.get_pixel(
+                (source.x + u * source.w) as u32,
+                (source.y + v * source.h) as u32,
+            );
+
+            Some(Color::new(
+                texel.r * color.r,
+                texel.g * color.g,
+                texel.b * color.b,
+                texel.a * color.a,
+            ))
+        });
+
+        self.texture_atlas = texture_atlas;
+    }
+
+    /// Fills every triangle in `mesh` with `color`, blending each pixel once even where triangles
+    /// overlap.
+    pub fn draw_mesh(&mut self, mesh: &Mesh, color: Color) {
+        let width = self.image.width as usize;
+        let mut covered = vec![false; width * self.image.height as usize];
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] =
+                [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].position.truncate());
+
+            let min = a.min(b).min(c);
+            let max = a.max(b).max(c);
+
+            let origin = self.origin;
+
+            let min = (min - origin).floor().max(Vec2::ZERO);
+            let max = (max - origin)
+                .ceil()
+                .min(vec2(self.image.width as f32, self.image.height as f32));
+
+            for y in min.y as usize..max.y as usize {
+                for x in min.x as usize..max.x as usize {
+                    let center = origin + vec2(x as f32 + 0.5, y as f32 + 0.5);
+
+                    if is_in_triangle(center, a, b, c) {
+                        covered[y * width + x] = true;
+                    }
+                }
+            }
+        }
+
+        for (i, _) in covered.iter().enumerate().filter(|&(_, &covered)| covered) {
+            self.blend_pixel((i % width) as isize, (i / width) as isize, color);
+        }
+    }
+}
+
+fn is_in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    let side = |start: Vec2, end: Vec2| (end - start).perp_dot(point - start);
+    let sides = [side(a, b), side(b, c), side(c, a)];
+
+    sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0)
+}
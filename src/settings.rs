@@ -0,0 +1,102 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use crate::{
+    input::{Action, AimMode, KeyBindings},
+    level::filesystem,
+};
+
+pub const SETTINGS_FILE: &str = "settings.cfg";
+
+/// Player preferences, stored as `name = value` lines so they can be edited by hand.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub fullscreen: bool,
+    pub occlude_wall_shadows: bool,
+    pub aim_mode: AimMode,
+    /// Stored as `bind.<action> = <binding>, <binding>, ...` lines.
+    pub key_bindings: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fullscreen: crate::START_IN_FULLSCREEN,
+            occlude_wall_shadows: true,
+            aim_mode: AimMode::default(),
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Falls back to the default for anything missing or malformed.
+    pub fn load() -> Self {
+        let mut settings = Self::default();
+
+        if let Ok(data) = filesystem::load_save_file(SETTINGS_FILE) {
+            for line in String::from_utf8_lossy(&data).lines() {
+                settings.apply_line(line);
+            }
+        }
+
+        settings
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        filesystem::write_save_file(SETTINGS_FILE, self.to_string().as_bytes())
+    }
+
+    pub fn apply_line(&mut self, line: &str) {
+        let line = line.split('#').next().unwrap_or_default();
+
+        let Some((name, value)) = line.split_once('=') else {
+            return;
+        };
+        let value = value.trim();
+
+        match name.trim() {
+            "fullscreen" => {
+                if let Ok(value) = value.parse() {
+                    self.fullscreen = value;
+                }
+            }
+            "occlude_wall_shadows" => {
+                if let Ok(value) = value.parse() {
+                    self.occlude_wall_shadows = value;
+                }
+            }
+            "aim_mode" => {
+                if let Some(value) = AimMode::from_name(value) {
+                    self.aim_mode = value;
+                }
+            }
+            name => {
+                if let Some(action) = name.strip_prefix("bind.").and_then(Action::from_name) {
+                    self.key_bindings.set_from_str(action, value);
+                }
+            }
+        }
+    }
+}
+
+impl Display for Settings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "fullscreen = {}", self.fullscreen)?;
+        writeln!(f, "occlude_wall_shadows = {}", self.occlude_wall_shadows)?;
+        writeln!(f, "aim_mode = {}", self.aim_mode.name())?;
+
+        for action in Action::ALL {
+            writeln!(
+                f,
+                "bind.{} = {}",
+                action.name(),
+                self.key_bindings.bindings_to_string(action)
+            )?;
+        }
+
+        Ok(())
+    }
+}